@@ -0,0 +1,28 @@
+#!/bin/sh
+# SPDX-License-Identifier: GPL-2.0
+#
+# Boots a kernel in a QEMU VM (with virtme-ng), loads the module with emulated
+# devices and runs the uAPI tests, exiting with their status.
+#
+# Usage: KDIR=/path/to/linux tools/uapi-test/run-qemu.sh [emulated devices]
+#
+# The kernel must have been built from the patched tree, and the module built
+# against it (with `make KDIR=...`).
+
+set -eu
+
+here=$(cd "$(dirname "$0")" && pwd)
+root=$(cd "$here/../.." && pwd)
+kdir=${KDIR:?KDIR must be set to the kernel build directory}
+count=${1:-2}
+module="$root/build/tt_core.ko"
+
+if [ ! -f "$module" ]; then
+    echo "$module not found; build the module first" >&2
+    exit 1
+fi
+
+cargo build --release --manifest-path "$here/Cargo.toml"
+harness="$here/target/release/tt-uapi-test"
+
+exec vng --run "$kdir" --user root --exec "insmod $module emulated_devices=$count unsafe_flash_access=1 && $harness"
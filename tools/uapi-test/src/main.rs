@@ -0,0 +1,584 @@
+// SPDX-License-Identifier: GPL-2.0
+
+//! Integration tests for the uAPI of the tenstorrent device nodes.
+//!
+//! Opens each `/dev/tenstorrent/N` (or the device nodes given as arguments),
+//! exercises the implemented ioctls and file operations with valid and invalid
+//! arguments, and reports the results in TAP format. The tests need a loaded
+//! `tt_core` module, but no hardware: emulated devices (`emulated_devices=N`)
+//! suffice, in which case the tests which need interrupt vectors are skipped.
+
+mod sys;
+mod tap;
+mod uapi;
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Read,
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use crate::{
+    sys::{EAGAIN, EBADF, EBUSY, EFAULT, EINVAL, ENOTTY, ENXIO, EOPNOTSUPP, EPERM, ERANGE, EROFS},
+    tap::{Outcome, Tap},
+    uapi::{
+        BindEventfd,
+        Event,
+        FLASH_BUNDLE_MAGIC,
+        FLASH_BUNDLE_VERSION,
+        FlashBundle,
+        FlashInfo,
+        FlashIo,
+        Harvesting,
+        IOCTL_BIND_EVENTFD,
+        IOCTL_FLASH_ERASE,
+        IOCTL_FLASH_INFO,
+        IOCTL_FLASH_READ,
+        IOCTL_FLASH_WRITE,
+        IOCTL_GET_HARVESTING,
+        IOCTL_GET_POWER_LIMITS,
+        IOCTL_GET_TOPOLOGY,
+        IOCTL_SET_POWER_LIMITS,
+        IOCTL_SET_POWER_STATE,
+        IRQ_SOURCES,
+        MAX_ETH_LINKS,
+        POWER_STATE_BUSY,
+        POWER_STATE_IDLE,
+        PowerInfo,
+        PowerLimits,
+        PowerState,
+        Topology,
+    },
+};
+
+/// The directory of the device nodes.
+const DEVICE_DIR: &str = "/dev/tenstorrent";
+
+/// An ioctl number of the driver extensions which is not implemented.
+const IOCTL_UNKNOWN: u32 = (0xFA << 8) | 0xFF;
+
+/// The number of times a device node is opened and closed in a row.
+const REOPEN_COUNT: usize = 256;
+
+fn main() -> ExitCode {
+    let mut tap = Tap::new();
+    let devices = match devices() {
+        Ok(devices) if !devices.is_empty() => devices,
+        Ok(_) => {
+            tap.skip_all(format!("no device nodes in {DEVICE_DIR}"));
+            return ExitCode::SUCCESS;
+        },
+        Err(err) => {
+            tap.skip_all(format!("cannot list {DEVICE_DIR}: {err}"));
+            return ExitCode::SUCCESS;
+        },
+    };
+
+    for path in &devices {
+        tap.note(format!("device {}", path.display()));
+        Device::new(&mut tap, path).run();
+    }
+
+    if tap.finish() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Returns the device nodes given as arguments, or all nodes in
+/// [`DEVICE_DIR`] (sorted by ordinal).
+///
+/// # Errors
+///
+/// * Errors if listing [`DEVICE_DIR`] fails.
+fn devices() -> std::io::Result<Vec<PathBuf>> {
+    let args = std::env::args_os().skip(1).map(PathBuf::from).collect::<Vec<_>>();
+    if !args.is_empty() {
+        return Ok(args);
+    }
+    let mut devices = std::fs::read_dir(DEVICE_DIR)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let ordinal = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            Some((ordinal, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    devices.sort_unstable();
+    Ok(devices.into_iter().map(|(_ordinal, path)| path).collect())
+}
+
+/// Returns the outcome of a call expected to fail with `errno`.
+fn expect_errno<T>(res: &Result<T, i32>, errno: i32) -> Outcome {
+    match *res {
+        Err(err) if err == errno => Outcome::Pass,
+        Err(err) => Outcome::Fail(format!("expected errno {errno}, got {err}")),
+        Ok(_) => Outcome::Fail(format!("expected errno {errno}, got success")),
+    }
+}
+
+/// Returns the outcome of a call expected to succeed.
+fn expect_ok<T>(res: &Result<T, i32>) -> Outcome {
+    match *res {
+        Ok(_) => Outcome::Pass,
+        Err(err) => Outcome::Fail(format!("expected success, got errno {err}")),
+    }
+}
+
+/// Returns the errno of a failed I/O operation.
+fn errno<T>(res: std::io::Result<T>) -> Result<T, i32> {
+    res.map_err(|err| err.raw_os_error().unwrap_or(0))
+}
+
+/// Binds the eventfd `fd` to `source` through `file`.
+fn bind(file: &File, source: u32, fd: i32) -> Result<i32, i32> {
+    let args = BindEventfd { source, fd };
+    sys::ioctl_ptr(file, IOCTL_BIND_EVENTFD, &raw const args)
+}
+
+/// The tests of a device node.
+struct Device<'tap> {
+    /// The reporter.
+    tap: &'tap mut Tap,
+    /// The path of the device node.
+    path: &'tap Path,
+}
+
+impl<'tap> Device<'tap> {
+    /// Creates the tests of the device node at `path`.
+    fn new(tap: &'tap mut Tap, path: &'tap Path) -> Self {
+        Self { tap, path }
+    }
+
+    /// Reports the test point `name` (qualified with the device node).
+    fn report(&mut self, name: &str, outcome: Outcome) {
+        let path = self.path.display();
+        self.tap.report(format!("{path}: {name}"), outcome);
+    }
+
+    /// Opens the device node read-write (control) or read-only (monitor),
+    /// with the additional open `flags`.
+    fn open(&self, write: bool, flags: i32) -> Result<File, i32> {
+        errno(
+            OpenOptions::new()
+                .read(true)
+                .write(write)
+                .custom_flags(flags)
+                .open(self.path),
+        )
+    }
+
+    /// Runs all tests of the device node.
+    fn run(mut self) {
+        let control = self.open(true, 0);
+        let monitor = self.open(false, 0);
+        let (control, monitor) = match (control, monitor) {
+            (Ok(control), Ok(monitor)) => {
+                self.report("open read-write and read-only", Outcome::Pass);
+                (control, monitor)
+            },
+            (Err(err), _) | (_, Err(err)) => {
+                self.report("open read-write and read-only", Outcome::Fail(format!("errno {err}")));
+                return;
+            },
+        };
+
+        self.ioctls(&control);
+        self.eventfds(&monitor);
+        self.topology(&monitor);
+        self.harvesting(&monitor);
+        self.flash(&control, &monitor);
+        self.power_limits(&control, &monitor);
+        self.power_state(&control, &monitor);
+        self.events();
+        self.reopen();
+    }
+
+    /// Tests the argument checking of the ioctls.
+    fn ioctls(&mut self, control: &File) {
+        let res = sys::ioctl_ptr(control, IOCTL_UNKNOWN, std::ptr::null::<u8>());
+        self.report("unknown ioctl fails with ENOTTY", expect_errno(&res, ENOTTY));
+
+        let res = sys::ioctl_ptr(control, IOCTL_BIND_EVENTFD, std::ptr::null::<BindEventfd>());
+        self.report(
+            "BIND_EVENTFD with a NULL argument fails with EFAULT",
+            expect_errno(&res, EFAULT),
+        );
+
+        let res = bind(control, IRQ_SOURCES, -1);
+        self.report(
+            "BIND_EVENTFD to an invalid source fails with EINVAL",
+            expect_errno(&res, EINVAL),
+        );
+
+        let res = bind(control, 0, -1);
+        self.report("BIND_EVENTFD unbinding an unbound source succeeds", expect_ok(&res));
+    }
+
+    /// Tests binding eventfds, and their release when the binding file is
+    /// closed.
+    fn eventfds(&mut self, other: &File) {
+        const NAMES: [&str; 5] = [
+            "BIND_EVENTFD with a non-eventfd fails with EINVAL",
+            "BIND_EVENTFD with a closed fd fails with EBADF",
+            "BIND_EVENTFD to a source bound by another file fails with EBUSY",
+            "closing the binding file releases its eventfds",
+            "BIND_EVENTFD unbinding succeeds",
+        ];
+
+        let eventfd = match sys::new_eventfd() {
+            Ok(eventfd) => eventfd,
+            Err(err) => {
+                self.report("create an eventfd", Outcome::Fail(format!("errno {err}")));
+                return;
+            },
+        };
+        let fd = std::os::fd::AsRawFd::as_raw_fd(&eventfd);
+        let owner = match self.open(true, 0) {
+            Ok(owner) => owner,
+            Err(err) => {
+                self.report("open another file", Outcome::Fail(format!("errno {err}")));
+                return;
+            },
+        };
+
+        match bind(&owner, 0, fd) {
+            Ok(_) => self.report("BIND_EVENTFD binds an eventfd", Outcome::Pass),
+            Err(ENXIO) => {
+                let reason = "no interrupt vectors (e.g., an emulated device or unsafe_irq_sources=0)";
+                self.report("BIND_EVENTFD binds an eventfd", Outcome::Skip(reason.into()));
+                for name in NAMES {
+                    self.report(name, Outcome::Skip(reason.into()));
+                }
+                return;
+            },
+            Err(err) => {
+                self.report("BIND_EVENTFD binds an eventfd", Outcome::Fail(format!("errno {err}")));
+                return;
+            },
+        }
+
+        let owner_fd = std::os::fd::AsRawFd::as_raw_fd(&owner);
+        self.report(NAMES[0], expect_errno(&bind(&owner, 0, owner_fd), EINVAL));
+        self.report(NAMES[1], expect_errno(&bind(&owner, 0, i32::MAX), EBADF));
+        self.report(NAMES[2], expect_errno(&bind(other, 0, fd), EBUSY));
+        drop(owner);
+        self.report(NAMES[3], expect_ok(&bind(other, 0, fd)));
+        self.report(NAMES[4], expect_ok(&bind(other, 0, -1)));
+    }
+
+    /// Tests reading the Ethernet topology.
+    fn topology(&mut self, file: &File) {
+        let mut topology = Topology::default();
+        let res = sys::ioctl_ptr(file, IOCTL_GET_TOPOLOGY, &raw mut topology);
+        match res {
+            Ok(_) => {
+                let count = usize::try_from(topology.link_count).unwrap_or(usize::MAX);
+                let outcome = if count > MAX_ETH_LINKS {
+                    Outcome::Fail(format!("link count {count}"))
+                } else if let Some(link) = topology.links[.. count].iter().find(|link| link.state > 2) {
+                    Outcome::Fail(format!("link state {}", link.state))
+                } else {
+                    Outcome::Pass
+                };
+                self.report("GET_TOPOLOGY returns valid links", outcome);
+            },
+            Err(EOPNOTSUPP) => {
+                let reason = "no Ethernet topology (architecture not supported)";
+                self.report("GET_TOPOLOGY returns valid links", Outcome::Skip(reason.into()));
+                self.report(
+                    "GET_TOPOLOGY with a NULL argument fails with EFAULT",
+                    Outcome::Skip(reason.into()),
+                );
+                return;
+            },
+            Err(err) => {
+                self.report(
+                    "GET_TOPOLOGY returns valid links",
+                    Outcome::Fail(format!("errno {err}")),
+                );
+                return;
+            },
+        }
+
+        let res = sys::ioctl_ptr(file, IOCTL_GET_TOPOLOGY, std::ptr::null::<Topology>());
+        self.report(
+            "GET_TOPOLOGY with a NULL argument fails with EFAULT",
+            expect_errno(&res, EFAULT),
+        );
+    }
+
+    /// Tests reading the harvesting.
+    fn harvesting(&mut self, file: &File) {
+        const NAME: &str = "GET_HARVESTING returns masks within the grid";
+
+        let mut harvesting = Harvesting::default();
+        let res = sys::ioctl_ptr(file, IOCTL_GET_HARVESTING, &raw mut harvesting);
+        let outcome = match res {
+            Ok(_) => {
+                let masks = [
+                    ("tensix_rows", harvesting.tensix_rows, harvesting.tensix_row_count),
+                    ("tensix_cols", harvesting.tensix_cols, harvesting.tensix_col_count),
+                    ("dram_banks", harvesting.dram_banks, harvesting.dram_bank_count),
+                    ("eth_cores", harvesting.eth_cores, harvesting.eth_core_count),
+                ];
+                let outside = masks
+                    .iter()
+                    .find(|&&(_, mask, count)| mask.checked_shr(count).unwrap_or(0) != 0);
+                match outside {
+                    Some((name, mask, count)) => Outcome::Fail(format!("{name} {mask:#x} of {count}")),
+                    None => Outcome::Pass,
+                }
+            },
+            Err(EOPNOTSUPP) => Outcome::Skip("no harvesting (architecture not supported)".into()),
+            Err(err) => Outcome::Fail(format!("errno {err}")),
+        };
+        self.report(NAME, outcome);
+    }
+
+    /// Tests reading the SPI flash and the guards on erasing and writing it.
+    ///
+    /// NOTE: Nothing is erased or written: erases are of empty ranges and
+    /// bundles are invalid.
+    fn flash(&mut self, control: &File, monitor: &File) {
+        const NAMES: [&str; 5] = [
+            "FLASH_INFO describes a valid layout",
+            "FLASH_READ of a misaligned range fails with EINVAL",
+            "FLASH_READ of the first block succeeds",
+            "FLASH_ERASE from a read-only file fails with EPERM",
+            "FLASH_WRITE of an invalid bundle fails with EINVAL",
+        ];
+
+        let mut info = FlashInfo::default();
+        match sys::ioctl_ptr(monitor, IOCTL_FLASH_INFO, &raw mut info) {
+            Ok(_) => {},
+            Err(EOPNOTSUPP) => {
+                for name in NAMES {
+                    self.report(
+                        name,
+                        Outcome::Skip("no flash layout (architecture not supported)".into()),
+                    );
+                }
+                return;
+            },
+            Err(err) => {
+                self.report(NAMES[0], Outcome::Fail(format!("errno {err}")));
+                return;
+            },
+        }
+        let valid = info.block_size.is_power_of_two()
+            && info.size % info.block_size == 0
+            && info.writable_start % info.block_size == 0
+            && info.writable_end % info.block_size == 0
+            && info.writable_start <= info.writable_end
+            && info.writable_end <= info.size;
+        let outcome = if valid {
+            Outcome::Pass
+        } else {
+            Outcome::Fail(format!("{info:?}"))
+        };
+        self.report(NAMES[0], outcome);
+        if !valid {
+            return;
+        }
+
+        let mut block = vec![0u8; usize::try_from(info.block_size).unwrap_or(0)];
+        let data = u64::try_from(block.as_mut_ptr().addr()).unwrap_or(0);
+        let args = FlashIo {
+            offset: 1,
+            length: info.block_size,
+            data,
+        };
+        let res = sys::ioctl_ptr(monitor, IOCTL_FLASH_READ, &raw const args);
+        self.report(NAMES[1], expect_errno(&res, EINVAL));
+
+        let args = FlashIo { offset: 0, ..args };
+        let res = sys::ioctl_ptr(monitor, IOCTL_FLASH_READ, &raw const args);
+        let outcome = match res {
+            Err(EOPNOTSUPP) => Outcome::Skip("flash not accessible (unsupported or unsafe_flash_access unset)".into()),
+            res => expect_ok(&res),
+        };
+        self.report(NAMES[2], outcome);
+
+        let empty = FlashIo {
+            offset: info.writable_start,
+            length: 0,
+            data: 0,
+        };
+        if info.write_protected != 0 {
+            let res = sys::ioctl_ptr(control, IOCTL_FLASH_ERASE, &raw const empty);
+            self.report(
+                "FLASH_ERASE while write-protected fails with EROFS",
+                expect_errno(&res, EROFS),
+            );
+            self.report(NAMES[3], Outcome::Skip("write-protected".into()));
+            self.report(NAMES[4], Outcome::Skip("write-protected".into()));
+            return;
+        }
+
+        let res = sys::ioctl_ptr(monitor, IOCTL_FLASH_ERASE, &raw const empty);
+        self.report(NAMES[3], expect_errno(&res, EPERM));
+
+        let bundle = FlashBundle {
+            magic: FLASH_BUNDLE_MAGIC,
+            version: FLASH_BUNDLE_VERSION,
+            device_id: 0,
+            offset: info.writable_start,
+            length: 0,
+            signature_length: 0,
+        };
+        let args = FlashIo {
+            offset: 0,
+            length: u32::try_from(size_of::<FlashBundle>()).unwrap_or(0),
+            data: u64::try_from((&raw const bundle).addr()).unwrap_or(0),
+        };
+        let res = sys::ioctl_ptr(control, IOCTL_FLASH_WRITE, &raw const args);
+        let outcome = match res {
+            Err(EPERM) => Outcome::Skip("needs CAP_SYS_ADMIN".into()),
+            res => expect_errno(&res, EINVAL),
+        };
+        self.report(NAMES[4], outcome);
+    }
+
+    /// Tests reading the power and clock limits and the checks on setting
+    /// them.
+    ///
+    /// NOTE: Nothing is changed: the limits are only set to their current
+    /// values or to invalid ones.
+    fn power_limits(&mut self, control: &File, monitor: &File) {
+        const NAMES: [&str; 5] = [
+            "GET_POWER_LIMITS returns limits within the bounds",
+            "SET_POWER_LIMITS from a read-only file fails with EPERM",
+            "SET_POWER_LIMITS of the current limits succeeds",
+            "SET_POWER_LIMITS with aiclk_min above aiclk_max fails with EINVAL",
+            "SET_POWER_LIMITS beyond the bounds fails with ERANGE",
+        ];
+
+        let mut info = PowerInfo::default();
+        match sys::ioctl_ptr(monitor, IOCTL_GET_POWER_LIMITS, &raw mut info) {
+            Ok(_) => {},
+            Err(EOPNOTSUPP) => {
+                for name in NAMES {
+                    self.report(name, Outcome::Skip("no power limits (board not known)".into()));
+                }
+                return;
+            },
+            Err(err) => {
+                self.report(NAMES[0], Outcome::Fail(format!("errno {err}")));
+                return;
+            },
+        }
+        let within = |limits: &PowerLimits| {
+            (info.min.aiclk_min ..= info.max.aiclk_min).contains(&limits.aiclk_min)
+                && (info.min.aiclk_max ..= info.max.aiclk_max).contains(&limits.aiclk_max)
+                && (info.min.power_limit ..= info.max.power_limit).contains(&limits.power_limit)
+                && (info.min.thermal_limit ..= info.max.thermal_limit).contains(&limits.thermal_limit)
+                && limits.aiclk_min <= limits.aiclk_max
+        };
+        let outcome = if within(&info.limits) && within(&info.defaults) {
+            Outcome::Pass
+        } else {
+            Outcome::Fail(format!("{info:?}"))
+        };
+        self.report(NAMES[0], outcome);
+
+        let res = sys::ioctl_ptr(monitor, IOCTL_SET_POWER_LIMITS, &raw const info.limits);
+        self.report(NAMES[1], expect_errno(&res, EPERM));
+
+        let res = sys::ioctl_ptr(control, IOCTL_SET_POWER_LIMITS, &raw const info.limits);
+        if res == Err(EPERM) {
+            for name in &NAMES[2 ..] {
+                self.report(name, Outcome::Skip("needs CAP_SYS_ADMIN".into()));
+            }
+            return;
+        }
+        self.report(NAMES[2], expect_ok(&res));
+
+        let inverted = PowerLimits {
+            aiclk_min: info.max.aiclk_max,
+            aiclk_max: info.min.aiclk_min,
+            ..info.limits
+        };
+        let res = sys::ioctl_ptr(control, IOCTL_SET_POWER_LIMITS, &raw const inverted);
+        let outcome = if inverted.aiclk_min == inverted.aiclk_max {
+            Outcome::Skip("AI clock cannot be changed".into())
+        } else {
+            expect_errno(&res, EINVAL)
+        };
+        self.report(NAMES[3], outcome);
+
+        let beyond = PowerLimits {
+            power_limit: info.max.power_limit.saturating_add(1),
+            ..info.limits
+        };
+        let res = sys::ioctl_ptr(control, IOCTL_SET_POWER_LIMITS, &raw const beyond);
+        self.report(NAMES[4], expect_errno(&res, ERANGE));
+    }
+
+    /// Tests hinting the power state.
+    fn power_state(&mut self, control: &File, monitor: &File) {
+        const NAMES: [&str; 3] = [
+            "SET_POWER_STATE busy and idle succeeds",
+            "SET_POWER_STATE of an unknown state fails with EINVAL",
+            "SET_POWER_STATE from a read-only file fails with EPERM",
+        ];
+
+        let set = |file: &File, state: u32| {
+            let args = PowerState { state, reserved: 0 };
+            sys::ioctl_ptr(file, IOCTL_SET_POWER_STATE, &raw const args)
+        };
+        let res = set(control, POWER_STATE_BUSY).and_then(|_| set(control, POWER_STATE_IDLE));
+        let outcome = match res {
+            Err(EOPNOTSUPP) => Outcome::Skip("no power states (architecture not supported)".into()),
+            res => expect_ok(&res),
+        };
+        self.report(NAMES[0], outcome);
+
+        let res = set(control, 2);
+        self.report(NAMES[1], expect_errno(&res, EINVAL));
+
+        let res = set(monitor, POWER_STATE_BUSY);
+        self.report(NAMES[2], expect_errno(&res, EPERM));
+    }
+
+    /// Tests reading and polling the event queue of a new file.
+    fn events(&mut self) {
+        let file = match self.open(false, sys::O_NONBLOCK) {
+            Ok(file) => file,
+            Err(err) => {
+                self.report("open non-blocking", Outcome::Fail(format!("errno {err}")));
+                return;
+            },
+        };
+
+        let mut short = [0u8; size_of::<Event>() - 1];
+        let res = errno((&file).read(&mut short));
+        self.report(
+            "read into a buffer shorter than an event fails with EINVAL",
+            expect_errno(&res, EINVAL),
+        );
+
+        let mut buf = [0u8; size_of::<Event>()];
+        let res = errno((&file).read(&mut buf));
+        self.report(
+            "non-blocking read of an empty queue fails with EAGAIN",
+            expect_errno(&res, EAGAIN),
+        );
+
+        let outcome = match sys::poll_now(&file, sys::POLLIN) {
+            Ok(0) => Outcome::Pass,
+            Ok(revents) => Outcome::Fail(format!("revents {revents:#x}")),
+            Err(err) => Outcome::Fail(format!("errno {err}")),
+        };
+        self.report("poll of an empty queue is not readable", outcome);
+    }
+
+    /// Tests that files are released on close, by opening and closing the
+    /// device node repeatedly.
+    fn reopen(&mut self) {
+        let res = (0 .. REOPEN_COUNT).try_for_each(|_index| self.open(true, 0).map(drop));
+        self.report(&format!("open and close {REOPEN_COUNT} times"), expect_ok(&res));
+    }
+}
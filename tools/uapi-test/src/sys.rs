@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: The few libc functions needed are declared here (rather than depending
+// on the `libc` crate), so that the harness builds offline for a test image.
+
+use std::{
+    ffi::{c_int, c_short, c_uint, c_ulong},
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+unsafe extern "C" {
+    fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    fn eventfd(initval: c_uint, flags: c_int) -> c_int;
+    fn poll(fds: *mut PollFd, nfds: c_ulong, timeout: c_int) -> c_int;
+}
+
+/// The `EPERM` errno.
+pub const EPERM: i32 = 1;
+/// The `ENXIO` errno.
+pub const ENXIO: i32 = 6;
+/// The `EBADF` errno.
+pub const EBADF: i32 = 9;
+/// The `EAGAIN` errno.
+pub const EAGAIN: i32 = 11;
+/// The `EFAULT` errno.
+pub const EFAULT: i32 = 14;
+/// The `EBUSY` errno.
+pub const EBUSY: i32 = 16;
+/// The `EINVAL` errno.
+pub const EINVAL: i32 = 22;
+/// The `ENOTTY` errno.
+pub const ENOTTY: i32 = 25;
+/// The `EROFS` errno.
+pub const EROFS: i32 = 30;
+/// The `ERANGE` errno.
+pub const ERANGE: i32 = 34;
+/// The `EOPNOTSUPP` errno.
+pub const EOPNOTSUPP: i32 = 95;
+
+/// The `O_NONBLOCK` open flag.
+pub const O_NONBLOCK: i32 = 0o4000;
+
+/// The `POLLIN` poll event.
+pub const POLLIN: c_short = 0x1;
+
+/// The `EFD_CLOEXEC` eventfd flag.
+const EFD_CLOEXEC: c_int = 0o2_000_000;
+
+/// A `struct pollfd`.
+#[repr(C)]
+struct PollFd {
+    fd: c_int,
+    events: c_short,
+    revents: c_short,
+}
+
+/// Returns the errno of the last failed call.
+fn last_errno() -> i32 {
+    io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
+
+/// Issues the ioctl `cmd` with the (pointer) argument `arg` on `fd`.
+///
+/// # Errors
+///
+/// * Errors with the errno of a failed ioctl.
+pub fn ioctl_ptr<F: AsRawFd, T>(fd: &F, cmd: u32, arg: *const T) -> Result<c_int, i32> {
+    // SAFETY: The driver only accesses `arg` through `copy_from_user`, which
+    // fails gracefully for invalid pointers.
+    let ret = unsafe { ioctl(fd.as_raw_fd(), c_ulong::from(cmd), arg) };
+    if ret < 0 { Err(last_errno()) } else { Ok(ret) }
+}
+
+/// Creates an eventfd.
+///
+/// # Errors
+///
+/// * Errors with the errno of a failed `eventfd`.
+pub fn new_eventfd() -> Result<OwnedFd, i32> {
+    // SAFETY: FFI call without safety requirements.
+    let fd = unsafe { eventfd(0, EFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(last_errno());
+    }
+    // SAFETY: `fd` was just opened and is owned by nobody else.
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// Polls `fd` for `events` (without waiting) and returns the returned events.
+///
+/// # Errors
+///
+/// * Errors with the errno of a failed `poll`.
+pub fn poll_now<F: AsRawFd>(fd: &F, events: c_short) -> Result<c_short, i32> {
+    let mut pfd = PollFd {
+        fd: fd.as_raw_fd(),
+        events,
+        revents: 0,
+    };
+    // SAFETY: `pfd` is a valid array of one `struct pollfd`.
+    let ret = unsafe { poll(&raw mut pfd, 1, 0) };
+    if ret < 0 { Err(last_errno()) } else { Ok(pfd.revents) }
+}
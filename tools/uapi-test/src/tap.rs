@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use std::{
+    fmt::{Arguments, Display},
+    io::{StdoutLock, Write},
+};
+
+/// A reporter of test points in TAP (version 13) format.
+///
+/// The plan is printed last, once the number of test points is known.
+pub struct Tap {
+    /// The output (locked, so that the lines of other threads cannot interleave).
+    out: StdoutLock<'static>,
+    /// The number of test points reported.
+    count: usize,
+    /// The number of failed test points.
+    failed: usize,
+}
+
+/// The outcome of a test point.
+pub enum Outcome {
+    /// The test passed.
+    Pass,
+    /// The test failed, with a diagnostic.
+    Fail(String),
+    /// The test was skipped, with a reason.
+    Skip(String),
+}
+
+impl Tap {
+    /// Creates a [`Tap`] reporter and prints the version line.
+    pub fn new() -> Self {
+        let mut this = Self {
+            out: std::io::stdout().lock(),
+            count: 0,
+            failed: 0,
+        };
+        this.line(format_args!("TAP version 13"));
+        this
+    }
+
+    /// Prints the line `args`.
+    ///
+    /// NOTE: Failures to print are ignored, since they cannot be reported.
+    fn line(&mut self, args: Arguments) {
+        let _ = writeln!(self.out, "{args}");
+    }
+
+    /// Reports the test point `name` with `outcome`.
+    pub fn report<N: Display>(&mut self, name: N, outcome: Outcome) {
+        self.count += 1;
+        let count = self.count;
+        match outcome {
+            Outcome::Pass => self.line(format_args!("ok {count} - {name}")),
+            Outcome::Fail(diag) => {
+                self.failed += 1;
+                self.line(format_args!("not ok {count} - {name}"));
+                for line in diag.lines() {
+                    self.line(format_args!("# {line}"));
+                }
+            },
+            Outcome::Skip(reason) => self.line(format_args!("ok {count} - {name} # SKIP {reason}")),
+        }
+    }
+
+    /// Prints a diagnostic line.
+    pub fn note<N: Display>(&mut self, note: N) {
+        self.line(format_args!("# {note}"));
+    }
+
+    /// Prints the plan and returns `true` if no test point failed.
+    pub fn finish(mut self) -> bool {
+        let count = self.count;
+        self.line(format_args!("1..{count}"));
+        self.failed == 0
+    }
+
+    /// Prints an empty plan, skipping all tests for `reason`.
+    pub fn skip_all<R: Display>(mut self, reason: R) {
+        self.line(format_args!("1..0 # SKIP {reason}"));
+    }
+}
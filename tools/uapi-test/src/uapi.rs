@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: Mirrors `tt/uapi.rs` of the driver. Keep the two in sync.
+
+/// The ioctl type of the device nodes.
+const IOCTL_MAGIC: u32 = 0xFA;
+
+/// The first ioctl number of the extensions of the driver.
+const IOCTL_EXT_BASE: u32 = 0x80;
+
+/// The direction bit of ioctls which copy an argument from userspace.
+const IOC_WRITE: u32 = 1;
+
+/// The direction bit of ioctls which copy a result to userspace.
+const IOC_READ: u32 = 2;
+
+/// Returns the number of an ioctl with direction `dir` and an argument `T` (as
+/// the `_IOC` macro of `<asm-generic/ioctl.h>`).
+const fn ioc<T>(dir: u32, ty: u32, nr: u32) -> u32 {
+    #[allow(clippy::cast_possible_truncation, reason = "ioctl arguments are small")]
+    let size = size_of::<T>() as u32;
+    (dir << 30) | (size << 16) | (ty << 8) | nr
+}
+
+/// Binds an eventfd to an interrupt source (see [`BindEventfd`]).
+pub const IOCTL_BIND_EVENTFD: u32 = ioc::<BindEventfd>(IOC_WRITE, IOCTL_MAGIC, IOCTL_EXT_BASE);
+
+/// Returns the Ethernet topology of the chip (see [`Topology`]).
+pub const IOCTL_GET_TOPOLOGY: u32 = ioc::<Topology>(IOC_READ, IOCTL_MAGIC, IOCTL_EXT_BASE + 1);
+
+/// Returns the harvesting of the chip (see [`Harvesting`]).
+pub const IOCTL_GET_HARVESTING: u32 = ioc::<Harvesting>(IOC_READ, IOCTL_MAGIC, IOCTL_EXT_BASE + 2);
+
+/// Returns the layout of the SPI flash of the board (see [`FlashInfo`]).
+pub const IOCTL_FLASH_INFO: u32 = ioc::<FlashInfo>(IOC_READ, IOCTL_MAGIC, IOCTL_EXT_BASE + 3);
+
+/// Reads from the SPI flash of the board (see [`FlashIo`]).
+pub const IOCTL_FLASH_READ: u32 = ioc::<FlashIo>(IOC_WRITE, IOCTL_MAGIC, IOCTL_EXT_BASE + 4);
+
+/// Erases blocks of the SPI flash of the board (see [`FlashIo`]).
+pub const IOCTL_FLASH_ERASE: u32 = ioc::<FlashIo>(IOC_WRITE, IOCTL_MAGIC, IOCTL_EXT_BASE + 5);
+
+/// Writes a signed bundle to the SPI flash of the board (see [`FlashIo`] and
+/// [`FlashBundle`]).
+pub const IOCTL_FLASH_WRITE: u32 = ioc::<FlashIo>(IOC_WRITE, IOCTL_MAGIC, IOCTL_EXT_BASE + 6);
+
+/// Returns the power and clock limits of the chip and the bounds of its board
+/// (see [`PowerInfo`]).
+pub const IOCTL_GET_POWER_LIMITS: u32 = ioc::<PowerInfo>(IOC_READ, IOCTL_MAGIC, IOCTL_EXT_BASE + 7);
+
+/// Sets the power and clock limits of the chip (see [`PowerLimits`]).
+pub const IOCTL_SET_POWER_LIMITS: u32 = ioc::<PowerLimits>(IOC_WRITE, IOCTL_MAGIC, IOCTL_EXT_BASE + 8);
+
+/// Hints whether the file is about to use the chip (see [`PowerState`]).
+pub const IOCTL_SET_POWER_STATE: u32 = ioc::<PowerState>(IOC_WRITE, IOCTL_MAGIC, IOCTL_EXT_BASE + 9);
+
+/// The number of interrupt sources.
+pub const IRQ_SOURCES: u32 = 2;
+
+/// The argument of [`IOCTL_BIND_EVENTFD`].
+#[repr(C)]
+pub struct BindEventfd {
+    /// The interrupt source.
+    pub source: u32,
+    /// The eventfd, or negative to unbind.
+    pub fd: i32,
+}
+
+/// A device state change event, as read from the device nodes.
+///
+/// Kinds are numbered as follows:
+///
+/// * 1: events were dropped since the queue was full (`data` is the count).
+/// * 2: a reset of the chip started.
+/// * 3: a reset of the chip completed (`data` is the errno, or 0 on success).
+/// * 4: a PCI Express error was reported (`data` is the channel state: 1 for normal,
+///   2 for frozen and 3 for permanent failure).
+/// * 5: the firmware reported a fault.
+/// * 6: the firmware started throttling the chip, e.g. at its thermal limit
+///   (`data` is the throttler word of its telemetry).
+/// * 7: the chip is being suspended.
+/// * 8: the chip was resumed.
+/// * 9: the device was removed (no further events follow).
+/// * 10: the firmware heartbeat stopped (`data` is the time since it last
+///   changed in milliseconds).
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Event {
+    /// The kind of event.
+    pub kind: u32,
+    /// The state generation of the chip when the event occurred.
+    pub generation: u32,
+    /// The time of the event (`CLOCK_MONOTONIC`) in nanoseconds.
+    pub timestamp_ns: u64,
+    /// The data of the event (depending on the kind).
+    pub data: u64,
+}
+
+/// The maximum number of Ethernet links of a chip.
+pub const MAX_ETH_LINKS: usize = 16;
+
+/// The coordinates of a chip in an Ethernet-connected system.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct EthCoord {
+    /// The rack.
+    pub rack: u8,
+    /// The shelf within the rack.
+    pub shelf: u8,
+    /// The X coordinate within the shelf.
+    pub x: u8,
+    /// The Y coordinate within the shelf.
+    pub y: u8,
+}
+
+/// An Ethernet link of a chip.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct EthLink {
+    /// The state of the link (0: unknown, 1: not connected, 2: connected).
+    pub state: u32,
+    /// The port of the peer.
+    pub remote_port: u32,
+    /// The board ID of the peer.
+    pub remote_board_id: u64,
+    /// The coordinates of the peer.
+    pub remote: EthCoord,
+    /// Reserved (zero).
+    pub reserved: u32,
+}
+
+/// The result of [`IOCTL_GET_TOPOLOGY`].
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Topology {
+    /// The coordinates of the chip.
+    pub local: EthCoord,
+    /// The number of ports (valid entries of `links`).
+    pub link_count: u32,
+    /// The links, by port.
+    pub links: [EthLink; MAX_ETH_LINKS],
+}
+
+/// The result of [`IOCTL_GET_HARVESTING`].
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Harvesting {
+    /// The disabled Tensix rows.
+    pub tensix_rows: u32,
+    /// The disabled Tensix columns.
+    pub tensix_cols: u32,
+    /// The disabled DRAM banks.
+    pub dram_banks: u32,
+    /// The disabled Ethernet cores.
+    pub eth_cores: u32,
+    /// The number of Tensix rows of the grid.
+    pub tensix_row_count: u32,
+    /// The number of Tensix columns of the grid.
+    pub tensix_col_count: u32,
+    /// The number of DRAM banks of the grid.
+    pub dram_bank_count: u32,
+    /// The number of Ethernet cores of the grid.
+    pub eth_core_count: u32,
+    /// The harvesting mask as reported by the firmware.
+    pub raw: u32,
+    /// Reserved (zero).
+    pub reserved: u32,
+}
+
+/// The result of [`IOCTL_FLASH_INFO`].
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FlashInfo {
+    /// The size of the flash.
+    pub size: u32,
+    /// The size of a block.
+    pub block_size: u32,
+    /// The start of the writable region.
+    pub writable_start: u32,
+    /// The end of the writable region.
+    pub writable_end: u32,
+    /// Whether erasing and writing is refused.
+    pub write_protected: u32,
+    /// Reserved (zero).
+    pub reserved: u32,
+}
+
+/// The argument of [`IOCTL_FLASH_READ`], [`IOCTL_FLASH_ERASE`] and
+/// [`IOCTL_FLASH_WRITE`].
+#[repr(C)]
+pub struct FlashIo {
+    /// The offset within the flash.
+    pub offset: u32,
+    /// The length of the range or bundle.
+    pub length: u32,
+    /// The user address of the buffer or bundle.
+    pub data: u64,
+}
+
+/// The magic of a [`FlashBundle`] (`"TTFB"` in memory).
+pub const FLASH_BUNDLE_MAGIC: u32 = u32::from_le_bytes(*b"TTFB");
+
+/// The version of the [`FlashBundle`] layout.
+pub const FLASH_BUNDLE_VERSION: u32 = 1;
+
+/// The header of a bundle written with [`IOCTL_FLASH_WRITE`], followed by the
+/// signature and the image.
+#[repr(C)]
+pub struct FlashBundle {
+    /// The magic ([`FLASH_BUNDLE_MAGIC`]).
+    pub magic: u32,
+    /// The version of the layout ([`FLASH_BUNDLE_VERSION`]).
+    pub version: u32,
+    /// The PCI device ID of the chips the image is built for.
+    pub device_id: u32,
+    /// The offset of the image within the flash.
+    pub offset: u32,
+    /// The length of the image.
+    pub length: u32,
+    /// The length of the signature.
+    pub signature_length: u32,
+}
+
+/// The power and clock limits of a chip: the argument of
+/// [`IOCTL_SET_POWER_LIMITS`].
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct PowerLimits {
+    /// The minimum AI clock in MHz.
+    pub aiclk_min: u32,
+    /// The maximum AI clock in MHz.
+    pub aiclk_max: u32,
+    /// The power limit of the chip in watts.
+    pub power_limit: u32,
+    /// The temperature at which the chip is throttled in degrees Celsius.
+    pub thermal_limit: u32,
+}
+
+/// The result of [`IOCTL_GET_POWER_LIMITS`].
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct PowerInfo {
+    /// The current limits.
+    pub limits: PowerLimits,
+    /// The lower bounds of the limits.
+    pub min: PowerLimits,
+    /// The upper bounds of the limits.
+    pub max: PowerLimits,
+    /// The default limits of the board.
+    pub defaults: PowerLimits,
+}
+
+/// The [`PowerState`] of a file which does not use the chip.
+pub const POWER_STATE_IDLE: u32 = 0;
+
+/// The [`PowerState`] of a file which is about to use the chip.
+pub const POWER_STATE_BUSY: u32 = 1;
+
+/// The argument of [`IOCTL_SET_POWER_STATE`].
+#[repr(C)]
+pub struct PowerState {
+    /// The state of the file ([`POWER_STATE_IDLE`] or [`POWER_STATE_BUSY`]).
+    pub state: u32,
+    /// Reserved (zero).
+    pub reserved: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_eventfd_number() {
+        // _IOW(0xFA, 0x80, struct { u32; s32; })
+        assert_eq!(IOCTL_BIND_EVENTFD, 0x4008_FA80);
+    }
+
+    #[test]
+    fn get_topology_number() {
+        // _IOR(0xFA, 0x81, struct { 8 bytes; 16 * 24 bytes })
+        assert_eq!(IOCTL_GET_TOPOLOGY, 0x8188_FA81);
+    }
+
+    #[test]
+    fn get_harvesting_number() {
+        // _IOR(0xFA, 0x82, struct { 10 * u32 })
+        assert_eq!(IOCTL_GET_HARVESTING, 0x8028_FA82);
+    }
+
+    #[test]
+    fn flash_numbers() {
+        // _IOR(0xFA, 0x83, struct { 6 * u32 })
+        assert_eq!(IOCTL_FLASH_INFO, 0x8018_FA83);
+        // _IOW(0xFA, 0x84 ..= 0x86, struct { u32; u32; u64 })
+        assert_eq!(IOCTL_FLASH_READ, 0x4010_FA84);
+        assert_eq!(IOCTL_FLASH_ERASE, 0x4010_FA85);
+        assert_eq!(IOCTL_FLASH_WRITE, 0x4010_FA86);
+    }
+
+    #[test]
+    fn power_limits_numbers() {
+        // _IOR(0xFA, 0x87, struct { 4 * struct { 4 * u32 } })
+        assert_eq!(IOCTL_GET_POWER_LIMITS, 0x8040_FA87);
+        // _IOW(0xFA, 0x88, struct { 4 * u32 })
+        assert_eq!(IOCTL_SET_POWER_LIMITS, 0x4010_FA88);
+    }
+
+    #[test]
+    fn set_power_state_number() {
+        // _IOW(0xFA, 0x89, struct { u32; u32 })
+        assert_eq!(IOCTL_SET_POWER_STATE, 0x4008_FA89);
+    }
+
+    #[test]
+    fn flash_bundle_layout() {
+        assert_eq!(size_of::<FlashBundle>(), 24);
+        assert_eq!(FLASH_BUNDLE_MAGIC.to_le_bytes(), *b"TTFB");
+    }
+
+    #[test]
+    fn topology_layout() {
+        assert_eq!(size_of::<EthLink>(), 24);
+        assert_eq!(size_of::<Topology>(), 392);
+    }
+
+    #[test]
+    fn event_layout() {
+        assert_eq!(size_of::<Event>(), 24);
+    }
+}
@@ -2,15 +2,35 @@
 
 //! Tenstorrent device driver library.
 
+/// Definitions related to the cluster descriptor of the chips.
+#[cfg(CONFIG_DEBUG_FS)]
+pub(crate) mod cluster;
+
 /// Definitions related to compatibility with the official C driver module.
 pub(crate) mod compat;
 
+/// Definitions related to the debugfs tree of the driver.
+pub(crate) mod debugfs;
+
 /// Definitions related to the hardware devices and their registration.
 pub(crate) mod device;
 
 /// Definitions related to Tenstorrent hardware configurations.
 pub(crate) mod hw;
 
+/// Definitions related to putting chips into a safe state at reboot.
+pub(crate) mod reboot;
+
+/// Definitions related to the tracepoints of the driver.
+pub(crate) mod trace;
+
+/// Definitions related to the userspace API of the device nodes.
+pub(crate) mod uapi;
+
+/// Definitions related to verifying the signatures of images written to the
+/// chips.
+pub(crate) mod verify;
+
 /// Definitions related to the module version metadata.
 pub(crate) mod version;
 
@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::core::fmt;
+use ::kernel::prelude::*;
+
+use crate::tt::{reboot::TtReboot, uapi::EthCoord};
+
+/// A chip of a [`Cluster`].
+struct Chip {
+    /// The name of the architecture.
+    arch: &'static CStr,
+    /// The coordinates, if known.
+    coord: Option<EthCoord>,
+    /// The ordinal of the misc device, if the chip is local (memory-mapped by
+    /// the host rather than only reachable over Ethernet).
+    ordinal: Option<usize>,
+    /// The name of the board, if known.
+    board: Option<&'static CStr>,
+    /// The harvesting mask reported by the firmware, if known.
+    harvest_mask: Option<u32>,
+}
+
+/// An Ethernet connection between two (chip ID, port) endpoints, the lesser
+/// first.
+type Connection = [(usize, u32); 2];
+
+/// The cluster descriptor: the chips of the system and the Ethernet
+/// connections between them, in the YAML layout consumed by tt-metal (see
+/// `docs/usage/cluster-descriptor.md`).
+///
+/// Local chips are numbered first (by ordinal), followed by the remote chips
+/// seen as peers of their links (in order of discovery). A remote chip is
+/// identified by its coordinates, so a peer at the coordinates of a local chip
+/// is that chip.
+pub(crate) struct Cluster {
+    /// The chips, by chip ID.
+    chips: KVec<Chip>,
+    /// The Ethernet connections.
+    connections: KVec<Connection>,
+}
+
+impl Cluster {
+    /// Assembles the cluster descriptor from the state of all chips.
+    ///
+    /// Chips which have failed, or whose topology cannot be read (e.g., on
+    /// architectures without Ethernet support), are listed without
+    /// coordinates or connections. Likewise, chips whose harvesting cannot be
+    /// read are listed without it.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the descriptor fails.
+    pub(crate) fn collect() -> Result<Self> {
+        let mut local = TtReboot::chips()?;
+        local.sort_unstable_by_key(|chip| chip.ordinal());
+
+        let mut this = Self {
+            chips: KVec::with_capacity(local.len(), GFP_KERNEL)?,
+            connections: KVec::new(),
+        };
+        let mut topologies = KVec::with_capacity(local.len(), GFP_KERNEL)?;
+        for chip in &local {
+            let topology = chip.check_alive().and_then(|()| chip.topology()).ok();
+            let harvesting = chip.check_alive().and_then(|()| chip.harvesting()).ok();
+            this.chips.push(
+                Chip {
+                    arch: chip.hw().name,
+                    coord: topology.map(|topology| topology.local),
+                    ordinal: Some(chip.ordinal()),
+                    board: chip.board().map(|board| board.name),
+                    harvest_mask: harvesting.map(|harvesting| harvesting.raw),
+                },
+                GFP_KERNEL,
+            )?;
+            topologies.push((chip.hw().name, topology), GFP_KERNEL)?;
+        }
+
+        for (id, &(arch, ref topology)) in topologies.iter().enumerate() {
+            let Some(topology) = topology.as_ref() else {
+                continue;
+            };
+            let count = usize::try_from(topology.link_count).unwrap_or(0);
+            for (port, link) in (0u32 ..).zip(topology.links.iter().take(count)) {
+                // Only connected links (state 2) describe a peer.
+                if link.state != 2 {
+                    continue;
+                }
+                let peer = this.chip_at(link.remote, arch)?;
+                let mut connection = [(id, port), (peer, link.remote_port)];
+                connection.sort_unstable();
+                if !this.connections.contains(&connection) {
+                    this.connections.push(connection, GFP_KERNEL)?;
+                }
+            }
+        }
+
+        Ok(this)
+    }
+
+    /// Returns the ID of the chip at `coord`, adding a remote chip of `arch`
+    /// if there is none.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the chip fails.
+    fn chip_at(&mut self, coord: EthCoord, arch: &'static CStr) -> Result<usize> {
+        if let Some(id) = self.chips.iter().position(|chip| chip.coord == Some(coord)) {
+            return Ok(id);
+        }
+        let id = self.chips.len();
+        self.chips.push(
+            Chip {
+                arch,
+                coord: Some(coord),
+                ordinal: None,
+                board: None,
+                harvest_mask: None,
+            },
+            GFP_KERNEL,
+        )?;
+        Ok(id)
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "arch: {{")?;
+        for (id, chip) in self.chips.iter().enumerate() {
+            writeln!(f, "   {id}: {},", chip.arch)?;
+        }
+        writeln!(f, "}}\n\nchips: {{")?;
+        for (id, chip) in self.chips.iter().enumerate() {
+            if let Some(EthCoord { rack, shelf, x, y }) = chip.coord {
+                writeln!(f, "   {id}: [{x}, {y}, {rack}, {shelf}],")?;
+            }
+        }
+        writeln!(f, "}}\n\nethernet_connections: [")?;
+        for &[(chip0, chan0), (chip1, chan1)] in &self.connections {
+            writeln!(
+                f,
+                "   [{{chip: {chip0}, chan: {chan0}}}, {{chip: {chip1}, chan: {chan1}}}],"
+            )?;
+        }
+        writeln!(f, "]\n\nchips_with_mmio: [")?;
+        for (id, chip) in self.chips.iter().enumerate() {
+            if let Some(ordinal) = chip.ordinal {
+                writeln!(f, "   {id}: {ordinal},")?;
+            }
+        }
+        writeln!(f, "]\n\nharvesting: {{")?;
+        for (id, chip) in self.chips.iter().enumerate() {
+            if let Some(mask) = chip.harvest_mask {
+                writeln!(f, "   {id}: {{harvest_mask: {mask}}},")?;
+            }
+        }
+        writeln!(f, "}}\n\nboardtype: {{")?;
+        for (id, chip) in self.chips.iter().enumerate() {
+            if let Some(board) = chip.board {
+                writeln!(f, "   {id}: {board},")?;
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
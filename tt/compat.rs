@@ -1,10 +1,31 @@
 // SPDX-License-Identifier: GPL-2.0
 
-use ::core::ptr::NonNull;
+use ::core::{mem::MaybeUninit, ptr::NonNull};
 use ::kernel::{
-    bindings::{self, attribute, attribute_group, kobj_attribute, kobject},
+    bindings::{
+        self,
+        attribute,
+        attribute_group,
+        class,
+        device,
+        device_attribute,
+        kobj_attribute,
+        kobject,
+        module,
+        notifier_block,
+    },
+    ffi::{c_char, c_int, c_ulong, c_void},
+    pci,
     prelude::*,
-    types::Opaque,
+    sync::Arc,
+    time::{Delta, delay::fsleep},
+    types::{ARef, Opaque},
+    workqueue::{self, Work, WorkItem},
+};
+
+use crate::tt::{
+    device::chip::TtChip,
+    hw::telemetry::{Telemetry, TelemetryLayout},
 };
 
 /// Configuration state for compatibility with official driver.
@@ -12,24 +33,60 @@ use ::kernel::{
 /// This structure is responsible for creating an overlay in sysfs and elsewhere
 /// for compatibility with the official driver with respect to userland
 /// applications that may check for files like
-/// `/sys/module/tenstorrent/version`.
+/// `/sys/module/tenstorrent/version` or enumerate devices under
+/// `/sys/class/tenstorrent`.
+///
+/// While enabled, a module notifier tears down the overlay before an incoming
+/// `tenstorrent` module goes live and (optionally) recreates it once that
+/// module has unloaded. This makes it safe to load both drivers on the same
+/// machine.
 ///
 /// NOTE: This is primarily intended to facilitate easier development and
 /// testing with existing userspace applications. In the long run, this
 /// compatibility layer will likely be removed.
-#[derive(Default)]
 pub(crate) struct TenstorrentCompat {
-    /// The optionally initialized compatibility overlay.
-    #[allow(unused, reason = "resident kobject")]
-    overlay: Option<Overlay>,
+    /// Whether the overlay was requested (and thus the notifier registered).
+    enabled: bool,
+}
+
+impl Drop for TenstorrentCompat {
+    fn drop(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        let nb = Opaque::raw_get(&raw const NOTIFIER);
+        // SAFETY: `nb` was registered in `TenstorrentCompat::new`.
+        unsafe { bindings::unregister_module_notifier(nb) };
+
+        // Wait for a pending restore (if any) since it references module text.
+        let restore = STATE.lock().restore.take();
+        if let Some(restore) = restore {
+            // SAFETY: `restore` is alive so the pointer to the field is valid.
+            let work = unsafe { Work::raw_get(&raw const restore.work) };
+            // SAFETY: `work` was initialized by `RestoreWork::new`.
+            unsafe { bindings::flush_work(work) };
+        }
+
+        let mut state = STATE.lock();
+        state.module = None;
+        state.uninstall();
+    }
 }
 
 impl TenstorrentCompat {
+    /// The number of attempts made to recreate the overlay after the official
+    /// module starts unloading.
+    const RESTORE_ATTEMPTS: u32 = 50;
+    /// The interval between attempts to recreate the overlay.
+    const RESTORE_INTERVAL: Delta = Delta::from_millis(100);
+
     /// Creates a new [`TenstorrentCompat`].
     ///
-    /// This involves checking for the existence of `/sys/module/tenstorrent`
-    /// and, if it does not exist, installing a compatibility overlay at that
-    /// path by creating a corresponding [`kobject`].
+    /// This involves registering a module notifier, checking for the existence
+    /// of `/sys/module/tenstorrent` and, if it does not exist, installing a
+    /// compatibility overlay at that path by creating a corresponding
+    /// [`kobject`].
     ///
     /// The purpose of this compatibility overlay is for easier integration with
     /// existing userspace tools, so that they can be used without significant
@@ -40,46 +97,100 @@ impl TenstorrentCompat {
     ///
     /// # Errors
     ///
-    /// * Errors if [`Self::should_enable_overlay`] fails.
+    /// * Errors if `register_module_notifier` fails.
+    /// * Errors if [`Self::sys_module_tenstorrent_exists`] fails.
+    /// * Errors if installing the [`Overlay`] fails.
     ///
     /// [`kobject`]: ::kernel::uapi::kobject
     pub(crate) fn new(module: &'static ThisModule) -> Result<Self> {
-        let overlay = Self::should_enable_overlay(module)?
-            .then(|| Overlay::new(module))
-            .transpose()
-            .or_else(|err| {
-                if Self::sys_module_tenstorrent_exists(module)? {
-                    Ok(None)
-                } else {
-                    Err(err)
-                }
-            })?;
-        Ok(Self { overlay })
-    }
+        // SAFETY: `new` is only called once, from the module initializer.
+        unsafe { STATE.init() };
 
-    /// Returns `true` if the compatibility overlay should be enabled.
-    ///
-    /// # Errors
-    ///
-    /// * Errors if [`Self::sys_module_tenstorrent_exists`] fails.
-    fn should_enable_overlay(this: &'static ThisModule) -> Result<bool> {
         if 0 == *crate::module_parameters::unsafe_compatibility_overlay.value() {
-            return Ok(false);
+            return Ok(Self { enabled: false });
+        }
+        pr_warn!("unsafe_compatibility_overlay=1\n");
+
+        // Register the notifier first so that a concurrently loading official
+        // module is either seen by the check below or displaces the overlay.
+        let nb = Opaque::raw_get(&raw const NOTIFIER);
+        // SAFETY: `nb` is a static and is unregistered on drop.
+        let err = unsafe { bindings::register_module_notifier(nb) };
+        ::kernel::error::to_result(err)?;
+        let this = Self { enabled: true };
+
+        let mut state = STATE.lock();
+        state.module = Some(module);
+        if Self::sys_module_tenstorrent_exists(module)? {
+            pr_warn!("deferring compatibility overlay; tenstorrent module already loaded\n");
+        } else {
+            state.install()?;
+        }
+        drop(state);
+
+        Ok(this)
+    }
+
+    /// Handles a module state change for the official `tenstorrent` module.
+    extern "C" fn notify(_nb: *mut notifier_block, action: c_ulong, data: *mut c_void) -> c_int {
+        // SAFETY: The module notifier chain always passes a valid `struct module`.
+        let name = unsafe { &raw const (*data.cast::<module>()).name };
+        // SAFETY: The module name is NUL-terminated.
+        let name = unsafe { CStr::from_char_ptr(name.cast()) };
+
+        if name == Overlay::MODULE_NAME {
+            match u32::try_from(action) {
+                Ok(bindings::module_state_MODULE_STATE_COMING) => Self::displace(),
+                Ok(bindings::module_state_MODULE_STATE_GOING) => Self::restore(),
+                Ok(_) | Err(_) => {},
+            }
         }
-        pr_warn!("unsafe_compatibility_overlay=1");
-        if Self::sys_module_tenstorrent_exists(this)? {
-            pr_warn!("disabling compatibility overlay; tenstorrent module already loaded");
-            return Ok(false);
+
+        #[allow(clippy::as_conversions, reason = "kernel constant")]
+        let done = bindings::NOTIFY_DONE as c_int;
+        done
+    }
+
+    /// Tears down the overlay before the official module creates its own entries.
+    fn displace() {
+        let mut state = STATE.lock();
+        if state.overlay.is_some() {
+            pr_warn!("removing compatibility overlay; tenstorrent module is loading\n");
+            state.uninstall();
+        }
+    }
+
+    /// Schedules the overlay to be recreated once the official module is gone.
+    fn restore() {
+        if 0 == *crate::module_parameters::unsafe_compatibility_overlay_restore.value() {
+            return;
+        }
+
+        let mut state = STATE.lock();
+        if state.module.is_none() || state.restore.is_some() {
+            return;
+        }
+
+        match RestoreWork::new() {
+            Ok(work) => {
+                if workqueue::system().enqueue(Arc::clone(&work)).is_ok() {
+                    state.restore = Some(work);
+                }
+            },
+            Err(err) => {
+                pr_err!("failed to schedule compatibility overlay restore: {err:?}\n");
+            },
         }
-        Ok(true)
     }
 
     /// Returns `Ok(true)` if `/sys/module/tenstorrent` exists.
     ///
+    /// This is used at initialization (where the notifier cannot see modules
+    /// loaded earlier) and while waiting for an unloading module to go away.
+    ///
     /// # Errors
     ///
-    /// * Errors if the path in question is found (by a call to `filp_open`) but
-    ///   the subsequent call to `filp_close` somehow fails.
+    /// * Errors if the `kset` for `/sys/module` can not be found.
     fn sys_module_tenstorrent_exists(this: &'static ThisModule) -> Result<bool> {
         let this = this.as_ptr();
 
@@ -106,8 +217,156 @@ impl TenstorrentCompat {
     }
 }
 
+/// The mutable state of the compatibility layer.
+struct CompatState {
+    /// The module which requested the overlay, if the overlay is wanted.
+    module: Option<&'static ThisModule>,
+    /// The installed overlay, if any.
+    overlay: Option<Overlay>,
+    /// The devices which have entries while the overlay is installed.
+    devices: KVec<CompatEntry>,
+    /// The pending restore work, if any.
+    restore: Option<Arc<RestoreWork>>,
+}
+
+impl CompatState {
+    /// Creates an empty [`CompatState`].
+    const fn new() -> Self {
+        Self {
+            module: None,
+            overlay: None,
+            devices: KVec::new(),
+            restore: None,
+        }
+    }
+
+    /// Installs the overlay (if wanted and not already installed) along with
+    /// the class device entries for all known devices.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if [`Overlay::new`] fails.
+    fn install(&mut self) -> Result {
+        let Some(module) = self.module else {
+            return Ok(());
+        };
+        if self.overlay.is_some() {
+            return Ok(());
+        }
+
+        self.overlay = Some(Overlay::new(module)?);
+        for entry in &mut self.devices {
+            entry.dev = ClassDevice::new(&entry.pdev, entry.name, &entry.chip)
+                .inspect_err(|err| pr_err!("failed to create compatibility entry: {err:?}\n"))
+                .ok();
+        }
+
+        Ok(())
+    }
+
+    /// Removes the class device entries and then the overlay itself.
+    fn uninstall(&mut self) {
+        for entry in &mut self.devices {
+            entry.dev = None;
+        }
+        self.overlay = None;
+    }
+}
+
+/// A device known to the compatibility layer.
+struct CompatEntry {
+    /// The devfs name of the corresponding misc device.
+    name: &'static CStr,
+    /// The PCI device the class device is parented to.
+    pdev: ARef<pci::Device>,
+    /// The class device, present while the overlay is installed.
+    dev: Option<ClassDevice>,
+    /// The state of the chip, shown by the attributes of the class device
+    /// (and thus dropped after it).
+    chip: Arc<TtChip>,
+}
+
+/// Deferred work which recreates the overlay after the official module unloads.
+#[pin_data]
+struct RestoreWork {
+    /// The work item.
+    #[pin]
+    work: Work<RestoreWork>,
+}
+
+::kernel::impl_has_work! {
+    impl HasWork<Self> for RestoreWork { self.work }
+}
+
+impl WorkItem for RestoreWork {
+    type Pointer = Arc<Self>;
+
+    fn run(_this: Arc<Self>) {
+        for _attempt in 0 .. TenstorrentCompat::RESTORE_ATTEMPTS {
+            {
+                let mut state = STATE.lock();
+                let Some(module) = state.module else {
+                    state.restore = None;
+                    return;
+                };
+                if state.overlay.is_some() {
+                    state.restore = None;
+                    return;
+                }
+                // The unloading module's sysfs entry is removed some time after
+                // the `GOING` notification, so wait for it to disappear.
+                if let Ok(false) = TenstorrentCompat::sys_module_tenstorrent_exists(module) {
+                    if let Err(err) = state.install() {
+                        pr_err!("failed to restore compatibility overlay: {err:?}\n");
+                    }
+                    state.restore = None;
+                    return;
+                }
+            }
+            fsleep(TenstorrentCompat::RESTORE_INTERVAL);
+        }
+
+        pr_warn!("giving up on restoring compatibility overlay\n");
+        STATE.lock().restore = None;
+    }
+}
+
+impl RestoreWork {
+    /// Creates a new [`RestoreWork`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocation fails.
+    fn new() -> Result<Arc<Self>> {
+        Arc::pin_init(
+            pin_init!(Self {
+                work <- ::kernel::new_work!("RestoreWork::work"),
+            }),
+            GFP_KERNEL,
+        )
+    }
+}
+
+::kernel::sync::global_lock! {
+    // SAFETY: Initialized in `TenstorrentCompat::new` before first use.
+    unsafe(uninit) static STATE: Mutex<CompatState> = CompatState::new();
+}
+
+/// The module notifier which tracks the official `tenstorrent` module.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut NOTIFIER: Opaque<notifier_block> = Opaque::new(notifier_block {
+    notifier_call: Some(TenstorrentCompat::notify),
+    // SAFETY: All-zeroes is a valid bit pattern for the remaining fields.
+    ..unsafe { MaybeUninit::zeroed().assume_init() }
+});
+
 /// The overlay structure for sysfs compatibility with the official Tenstorrent
 /// kernel-mode driver.
+///
+/// Besides `/sys/module/tenstorrent`, the overlay registers the `tenstorrent`
+/// device class. While the overlay is installed, each [`CompatDevice`] is also
+/// given a [`ClassDevice`] entry at `/sys/class/tenstorrent/tenstorrent!N`.
 pub(crate) struct Overlay {
     /// The kobject for the `/sys/module/tenstorrent` sysfs entry.
     kobj: NonNull<kobject>,
@@ -123,6 +382,10 @@ unsafe impl Sync for Overlay {}
 
 impl Drop for Overlay {
     fn drop(&mut self) {
+        let class = Opaque::raw_get(&raw const CLASS);
+        // SAFETY: `class` was registered in `Overlay::new` and all class devices
+        // have been destroyed by `CompatState::uninstall`.
+        unsafe { bindings::class_unregister(class) };
         let grp = Opaque::raw_get(&raw const ATTR_GROUP);
         // SAFETY: `kobj` and `grp` were only processed through Linux APIs.
         unsafe { bindings::sysfs_remove_group(self.kobj.as_ptr(), grp) };
@@ -143,6 +406,7 @@ impl Overlay {
     ///
     /// * Errors if [`Self::sys_module_tenstorrent_create`] fails.
     /// * Errors if `sysfs_create_group` fails.
+    /// * Errors if `class_register` fails.
     fn new(this: &'static ThisModule) -> Result<Self> {
         let kobj = Self::sys_module_tenstorrent_create(this)?;
         let attr_group = Opaque::raw_get(&raw const ATTR_GROUP);
@@ -154,6 +418,16 @@ impl Overlay {
             unsafe { bindings::kobject_put(kobj.as_ptr()) };
         })?;
 
+        let class = Opaque::raw_get(&raw const CLASS);
+        // SAFETY: `class` is a static which is only registered by the overlay.
+        let err = unsafe { bindings::class_register(class) };
+        ::kernel::error::to_result(err).inspect_err(|_err| {
+            // SAFETY: `kobj` and `attr_group` were only processed through Linux APIs.
+            unsafe { bindings::sysfs_remove_group(kobj.as_ptr(), attr_group) };
+            // SAFETY: `kobj` was created by `kobject_create_and_add`.
+            unsafe { bindings::kobject_put(kobj.as_ptr()) };
+        })?;
+
         pr_warn!("created tenstorrent compatibility overlay\n");
 
         Ok(Self { kobj })
@@ -175,6 +449,110 @@ impl Overlay {
     }
 }
 
+/// The registration of a PCI device with the compatibility layer.
+///
+/// The device is given a [`ClassDevice`] entry whenever the overlay is
+/// installed, and the entry is removed when this registration is dropped.
+pub(crate) struct CompatDevice {
+    /// The devfs name of the corresponding misc device.
+    name: &'static CStr,
+}
+
+impl Drop for CompatDevice {
+    fn drop(&mut self) {
+        let name = self.name;
+        STATE.lock().devices.retain(|entry| entry.name != name);
+    }
+}
+
+impl CompatDevice {
+    /// Registers `pdev` (named after the misc device) and its `chip` with the
+    /// compatibility layer.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the entry fails.
+    /// * Errors if creating the [`ClassDevice`] fails.
+    pub(crate) fn new(pdev: &pci::Device, name: &'static CStr, chip: &Arc<TtChip>) -> Result<Self> {
+        let mut state = STATE.lock();
+        let dev = if state.overlay.is_some() {
+            Some(ClassDevice::new(pdev, name, chip)?)
+        } else {
+            None
+        };
+        let pdev = ARef::from(pdev);
+        let chip = Arc::clone(chip);
+        state.devices.push(CompatEntry { name, pdev, dev, chip }, GFP_KERNEL)?;
+        Ok(Self { name })
+    }
+}
+
+/// A device entry at `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// The entry is parented to the PCI device, so the `device` link resolves to
+/// the PCI function (and its attributes) just as with the official driver. The
+/// entry has no device number; the device node itself is provided by the misc
+/// device at `/dev/tenstorrent/N`.
+///
+/// The entry has the `tt_*` attributes of the official driver which the
+/// driver can back (see [`CLASS_ATTRS`]). `tt_ttflash_ver` and
+/// `tt_fw_bundle_ver` are left out, since the telemetry words holding them are
+/// not known.
+struct ClassDevice {
+    /// The class device.
+    dev: NonNull<device>,
+}
+
+/// SAFETY: [`ClassDevice`] does not offer Rust access to non-[`Send`]
+/// internals throughout it's lifetime.
+unsafe impl Send for ClassDevice {}
+
+/// SAFETY: [`ClassDevice`] does not offer Rust access to non-[`Sync`]
+/// internals throughout it's lifetime.
+unsafe impl Sync for ClassDevice {}
+
+impl Drop for ClassDevice {
+    fn drop(&mut self) {
+        // SAFETY: `dev` was created by `device_create` and is only dropped once.
+        unsafe { bindings::device_unregister(self.dev.as_ptr()) };
+    }
+}
+
+impl ClassDevice {
+    /// Creates a [`ClassDevice`] for `pdev` (and its `chip`) named after the
+    /// misc device.
+    ///
+    /// The attributes show `chip`, which must thus outlive the class device.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if `device_create_with_groups` fails.
+    fn new(pdev: &pci::Device, name: &CStr, chip: &TtChip) -> Result<Self> {
+        let class = Opaque::raw_get(&raw const CLASS);
+        let parent = AsRef::<::kernel::device::Device>::as_ref(pdev).as_raw();
+        let data = ::core::ptr::from_ref(chip).cast_mut().cast::<c_void>();
+        let groups = (&raw mut CLASS_GROUPS).cast::<*const attribute_group>();
+        let fmt = ::kernel::c_str!("%s").as_char_ptr();
+        // SAFETY: Arguments are valid for API. The `/` in `name` is replaced
+        // with `!` by the kobject core, matching the official driver naming.
+        let dev =
+            unsafe { bindings::device_create_with_groups(class, parent, 0, data, groups, fmt, name.as_char_ptr()) };
+        let dev = ::kernel::error::from_err_ptr(dev)?;
+        let dev = NonNull::new(dev).ok_or(EIO)?;
+
+        Ok(Self { dev })
+    }
+}
+
+/// The `tenstorrent` class for `/sys/class/tenstorrent`.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut CLASS: Opaque<class> = Opaque::new(class {
+    name: c"tenstorrent".as_ptr().cast(),
+    // SAFETY: All-zeroes is a valid bit pattern for the remaining fields.
+    ..unsafe { MaybeUninit::zeroed().assume_init() }
+});
+
 /// The `version` attribute for `/sys/module/tenstorrent`.
 ///
 /// NOTE: We use `Opaque` to prevent implicit copies.
@@ -220,3 +598,344 @@ static mut ATTR_GROUP: Opaque<attribute_group> = {
     unsafe { attrs.write(ATTRS.as_ptr().cast_mut()) };
     this
 };
+
+/// Returns the chip shown by the class device `dev`.
+///
+/// # Safety
+///
+/// `dev` must be a [`ClassDevice`], valid for the duration of an attribute
+/// callback.
+unsafe fn chip_of<'dev>(dev: *mut device) -> Option<&'dev TtChip> {
+    // SAFETY: By the safety requirements, `dev` is valid.
+    let data = unsafe { bindings::dev_get_drvdata(dev) };
+    // SAFETY: The data of a `ClassDevice` is its chip, which outlives it.
+    unsafe { data.cast::<TtChip>().as_ref() }
+}
+
+/// Reads the telemetry of the chip of the class device `dev`, returning its
+/// layout and a snapshot.
+///
+/// # Safety
+///
+/// `dev` must be a [`ClassDevice`], valid for the duration of an attribute
+/// callback.
+///
+/// # Errors
+///
+/// * Errors with `ENODEV` if `dev` has no chip.
+/// * Errors with `EOPNOTSUPP` if the telemetry is not known for the chip.
+/// * Errors with `EIO` if the chip has failed.
+/// * Errors if reading the telemetry fails.
+unsafe fn telemetry(dev: *mut device) -> Result<(&'static TelemetryLayout, Telemetry)> {
+    // SAFETY: By the safety requirements, `dev` is a `ClassDevice`.
+    let chip = unsafe { chip_of(dev) }.ok_or(ENODEV)?;
+    let layout = chip.hw().telemetry.ok_or(EOPNOTSUPP)?;
+    chip.check_alive()?;
+    Ok((layout, chip.telemetry()?))
+}
+
+/// Reads the telemetry word selected by `index` for the class device `dev`.
+///
+/// # Safety
+///
+/// `dev` must be a [`ClassDevice`], valid for the duration of an attribute
+/// callback.
+///
+/// # Errors
+///
+/// * Errors with `EOPNOTSUPP` if the word is not known for the chip.
+/// * Errors if reading the telemetry fails (see [`telemetry`]).
+unsafe fn telemetry_word(dev: *mut device, index: fn(&TelemetryLayout) -> Option<usize>) -> Result<u32> {
+    // SAFETY: By the safety requirements, `dev` is a `ClassDevice`.
+    let (layout, telemetry) = unsafe { telemetry(dev) }?;
+    telemetry.word(index(layout).ok_or(EOPNOTSUPP)?)
+}
+
+/// Returns the result of an attribute callback which emitted `res`.
+fn emitted(res: Result<c_int>) -> isize {
+    #[allow(clippy::as_conversions, reason = "kernel returned value")]
+    let res = match res {
+        Ok(len) => len as isize,
+        Err(err) => err.to_errno() as isize,
+    };
+    res
+}
+
+/// Shows the clock selected by `index` (in MHz) of the class device `dev`.
+///
+/// # Safety
+///
+/// `dev` must be a [`ClassDevice`] and `buf` the page buffer of an attribute
+/// callback.
+unsafe fn show_clock(dev: *mut device, buf: *mut c_char, index: fn(&TelemetryLayout) -> Option<usize>) -> isize {
+    // SAFETY: By the safety requirements, `dev` is a `ClassDevice`.
+    let word = unsafe { telemetry_word(dev, index) };
+    emitted(word.map(|word| {
+        let fmt = ::kernel::c_str!("%u\n").as_char_ptr();
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        unsafe { bindings::sysfs_emit(buf, fmt, word & 0xFFFF) }
+    }))
+}
+
+/// Shows the firmware version selected by `index` of the class device `dev`,
+/// as its bytes from the most significant (e.g., `2.13.0.0`).
+///
+/// # Safety
+///
+/// `dev` must be a [`ClassDevice`] and `buf` the page buffer of an attribute
+/// callback.
+unsafe fn show_fw_version(dev: *mut device, buf: *mut c_char, index: fn(&TelemetryLayout) -> Option<usize>) -> isize {
+    // SAFETY: By the safety requirements, `dev` is a `ClassDevice`.
+    let word = unsafe { telemetry_word(dev, index) };
+    emitted(word.map(|word| {
+        let [major, minor, patch, build] = word.to_be_bytes().map(u32::from);
+        let fmt = ::kernel::c_str!("%u.%u.%u.%u\n").as_char_ptr();
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        unsafe { bindings::sysfs_emit(buf, fmt, major, minor, patch, build) }
+    }))
+}
+
+/// The `tt_card_type` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the name of the board (e.g., `n300`), as identified by its PCI
+/// subsystem device ID. Fails with `EOPNOTSUPP` if the board is not known.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_CARD_TYPE: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let chip = unsafe { chip_of(dev) }.ok_or(ENODEV);
+        let board = chip.and_then(|chip| chip.board().ok_or(EOPNOTSUPP));
+        emitted(board.map(|board| {
+            let fmt = ::kernel::c_str!("%s\n").as_char_ptr();
+            // SAFETY: Arguments are valid for API. Error is propagated on failure.
+            unsafe { bindings::sysfs_emit(buf, fmt, board.name.as_char_ptr()) }
+        }))
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_card_type".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `tt_serial` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the board ID from the telemetry of the chip in hexadecimal (e.g.,
+/// `0100014311720049`). Fails with `EOPNOTSUPP` if the board ID is not known.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_SERIAL: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let board_id = unsafe { telemetry(dev) }.and_then(|(layout, telemetry)| {
+            let (high, low) = layout.board_id.ok_or(EOPNOTSUPP)?;
+            Ok((telemetry.word(high)?, telemetry.word(low)?))
+        });
+        emitted(board_id.map(|(high, low)| {
+            let fmt = ::kernel::c_str!("%08X%08X\n").as_char_ptr();
+            // SAFETY: Arguments are valid for API. Error is propagated on failure.
+            unsafe { bindings::sysfs_emit(buf, fmt, high, low) }
+        }))
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_serial".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `tt_aiclk` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the current AI clock from the telemetry of the chip in MHz.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_AICLK: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_clock(dev, buf, |layout| layout.clocks.map(|(aiclk, _axiclk, _arcclk)| aiclk)) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_aiclk".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `tt_axiclk` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the current AXI clock from the telemetry of the chip in MHz.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_AXICLK: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_clock(dev, buf, |layout| layout.clocks.map(|(_aiclk, axiclk, _arcclk)| axiclk)) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_axiclk".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `tt_arcclk` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the current ARC clock from the telemetry of the chip in MHz.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_ARCCLK: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_clock(dev, buf, |layout| layout.clocks.map(|(_aiclk, _axiclk, arcclk)| arcclk)) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_arcclk".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `tt_arc_fw_ver` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the version of the ARC firmware from the telemetry of the chip.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_ARC_FW_VER: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_fw_version(dev, buf, |layout| layout.fw_versions.map(|(arc, _eth, _bl, _app)| arc)) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_arc_fw_ver".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `tt_eth_fw_ver` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the version of the Ethernet firmware from the telemetry of the chip.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_ETH_FW_VER: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_fw_version(dev, buf, |layout| layout.fw_versions.map(|(_arc, eth, _bl, _app)| eth)) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_eth_fw_ver".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `tt_m3bl_fw_ver` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the version of the M3 bootloader from the telemetry of the chip.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_M3BL_FW_VER: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_fw_version(dev, buf, |layout| layout.fw_versions.map(|(_arc, _eth, bl, _app)| bl)) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_m3bl_fw_ver".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `tt_m3app_fw_ver` attribute for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// Shows the version of the M3 application firmware from the telemetry of the
+/// chip.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_M3APP_FW_VER: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_fw_version(dev, buf, |layout| layout.fw_versions.map(|(_arc, _eth, _bl, app)| app)) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"tt_m3app_fw_ver".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// Returns the `attribute` of the device attribute `attr`.
+const fn attr_of(attr: *const Opaque<device_attribute>) -> *mut attribute {
+    let attr = Opaque::raw_get(attr);
+    // SAFETY: `attr` points at a device attribute.
+    unsafe { &raw mut (*attr).attr }
+}
+
+/// The attributes for `/sys/class/tenstorrent/tenstorrent!N`.
+const CLASS_ATTRS: [*mut attribute; 10] = [
+    attr_of(&raw const ATTR_CARD_TYPE),
+    attr_of(&raw const ATTR_SERIAL),
+    attr_of(&raw const ATTR_AICLK),
+    attr_of(&raw const ATTR_AXICLK),
+    attr_of(&raw const ATTR_ARCCLK),
+    attr_of(&raw const ATTR_ARC_FW_VER),
+    attr_of(&raw const ATTR_ETH_FW_VER),
+    attr_of(&raw const ATTR_M3BL_FW_VER),
+    attr_of(&raw const ATTR_M3APP_FW_VER),
+    ::core::ptr::null_mut(),
+];
+
+/// The attribute group for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut CLASS_ATTR_GROUP: Opaque<attribute_group> = Opaque::new(attribute_group {
+    attrs: CLASS_ATTRS.as_ptr().cast_mut(),
+    // SAFETY: All-zeroes is a valid bit pattern for the remaining fields.
+    ..unsafe { MaybeUninit::zeroed().assume_init() }
+});
+
+/// The attribute groups for `/sys/class/tenstorrent/tenstorrent!N`.
+///
+/// NOTE: Static, since the driver core keeps the pointer to the array until the
+/// class device is deleted.
+static mut CLASS_GROUPS: [*const attribute_group; 2] = [
+    Opaque::raw_get(&raw const CLASS_ATTR_GROUP).cast_const(),
+    ::core::ptr::null(),
+];
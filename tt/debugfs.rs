@@ -0,0 +1,506 @@
+// SPDX-License-Identifier: GPL-2.0
+
+#[cfg(CONFIG_DEBUG_FS)]
+use ::core::{marker::PhantomData, mem::MaybeUninit, ptr::NonNull};
+#[cfg(CONFIG_DEBUG_FS)]
+use ::kernel::{
+    bindings::{self, dentry, file, file_operations, inode, seq_file},
+    ffi::{c_int, c_void},
+    seq_file::SeqFile,
+    seq_print,
+    sync::atomic::{Atomic, Relaxed},
+};
+use ::kernel::{prelude::*, sync::Arc};
+
+use crate::tt::device::chip::TtChip;
+#[cfg(CONFIG_DEBUG_FS)]
+use crate::tt::{
+    cluster::Cluster,
+    device::misc::TtMisc,
+    hw::grid::{Coords, CoreKind},
+};
+
+/// The debugfs tree of the driver at `/sys/kernel/debug/tt_core`.
+///
+/// The root holds the `ordinals` file (the misc device ordinal map), the
+/// `cluster_descriptor` file (see [`Cluster`]) and a [`DeviceDir`] for each
+/// bound device.
+///
+/// NOTE: debugfs is a debugging aid, so failures to create entries are
+/// ignored (as is conventional) rather than failing module initialization.
+pub(crate) struct TtDebugfs;
+
+impl Drop for TtDebugfs {
+    fn drop(&mut self) {
+        #[cfg(CONFIG_DEBUG_FS)]
+        drop(ROOT.lock().take());
+    }
+}
+
+impl TtDebugfs {
+    /// Creates the debugfs root directory of the driver.
+    pub(crate) fn new() -> Self {
+        #[cfg(CONFIG_DEBUG_FS)]
+        {
+            // SAFETY: `new` is only called once, from the module initializer.
+            unsafe { ROOT.init() };
+            let root = Dir::new(::kernel::c_str!("tt_core"), ::core::ptr::null_mut());
+            let unit = NonNull::<()>::dangling().as_ptr();
+            root.show_file::<ShowOrdinals>(::kernel::c_str!("ordinals"), unit);
+            root.show_file::<ShowCluster>(::kernel::c_str!("cluster_descriptor"), unit);
+            *ROOT.lock() = Some(root);
+        }
+        Self
+    }
+}
+
+/// The debugfs directory of a device at `/sys/kernel/debug/tt_core/<name>`,
+/// named after the PCI device (e.g., `0000:05:00.0`) or the emulated device.
+///
+/// The directory holds the following files:
+///
+/// * `state`: the state of the chip.
+/// * `files`: the open files of the chip (ID, PID and access mode).
+/// * `arc_stats`: the statistics of messages to the ARC firmware.
+/// * `noc_grid`: the NOC grid of the chip (see [`crate::tt::hw::grid::Grid`]).
+/// * `tlbs`: the allocation of the TLB windows of the chip (see
+///   [`TtChip::tlb_allocation`]).
+/// * `reg_addr` and `reg_data`: a raw BAR0 register interface, only present
+///   with the `unsafe_debugfs_regs` parameter set. Reading or writing
+///   `reg_data` accesses the register at the offset written to `reg_addr`.
+#[allow(clippy::empty_structs_with_brackets, reason = "cfg")]
+#[allow(dead_code, reason = "drop guard")]
+pub(crate) struct DeviceDir {
+    /// The directory (removed before `data` is dropped).
+    #[cfg(CONFIG_DEBUG_FS)]
+    dir: Dir,
+    /// The data referenced by the files in the directory.
+    #[cfg(CONFIG_DEBUG_FS)]
+    data: KBox<DeviceData>,
+}
+
+impl DeviceDir {
+    /// Creates the debugfs directory `name` for `chip`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the data for the files fails.
+    #[cfg(CONFIG_DEBUG_FS)]
+    pub(crate) fn new(name: &CStr, chip: &Arc<TtChip>) -> Result<Self> {
+        let data = KBox::new(
+            DeviceData {
+                chip: Arc::clone(chip),
+                reg_addr: Atomic::new(0u32),
+            },
+            GFP_KERNEL,
+        )?;
+
+        let parent = ROOT.lock().as_ref().map_or(::core::ptr::null_mut(), |root| root.dentry);
+        let dir = Dir::new(name, parent);
+
+        let ptr = ::core::ptr::from_ref(&*data).cast_mut();
+        dir.show_file::<ShowState>(::kernel::c_str!("state"), ptr);
+        dir.show_file::<ShowFiles>(::kernel::c_str!("files"), ptr);
+        dir.show_file::<ShowArcStats>(::kernel::c_str!("arc_stats"), ptr);
+        dir.show_file::<ShowNocGrid>(::kernel::c_str!("noc_grid"), ptr);
+        dir.show_file::<ShowTlbs>(::kernel::c_str!("tlbs"), ptr);
+        if 0 != *crate::module_parameters::unsafe_debugfs_regs.value() {
+            dir.reg_files(ptr);
+        }
+
+        Ok(Self { dir, data })
+    }
+
+    /// Creates nothing, since debugfs is not enabled.
+    ///
+    /// # Errors
+    ///
+    /// * Never errors.
+    #[cfg(not(CONFIG_DEBUG_FS))]
+    pub(crate) fn new(name: &CStr, chip: &Arc<TtChip>) -> Result<Self> {
+        let _ = (name, chip);
+        Ok(Self {})
+    }
+}
+
+/// The data referenced by the files of a [`DeviceDir`].
+#[cfg(CONFIG_DEBUG_FS)]
+struct DeviceData {
+    /// The state of the chip.
+    chip: Arc<TtChip>,
+    /// The BAR0 offset accessed through `reg_data`.
+    reg_addr: Atomic<u32>,
+}
+
+/// A debugfs directory, removed (recursively) on drop.
+#[cfg(CONFIG_DEBUG_FS)]
+struct Dir {
+    /// The dentry of the directory (possibly an error pointer).
+    dentry: *mut dentry,
+}
+
+/// SAFETY: [`Dir`] does not offer Rust access to non-[`Send`] internals
+/// throughout it's lifetime.
+#[cfg(CONFIG_DEBUG_FS)]
+unsafe impl Send for Dir {}
+
+/// SAFETY: [`Dir`] does not offer Rust access to non-[`Sync`] internals
+/// throughout it's lifetime.
+#[cfg(CONFIG_DEBUG_FS)]
+unsafe impl Sync for Dir {}
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Drop for Dir {
+    fn drop(&mut self) {
+        // SAFETY: `dentry` was returned by `debugfs_create_dir`. Error pointers
+        // are ignored. Removal waits for callbacks in progress.
+        unsafe { bindings::debugfs_remove(self.dentry) };
+    }
+}
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Dir {
+    /// The file mode for read-only files.
+    const MODE_RO: u16 = 0o400;
+    /// The file mode for read-write files.
+    const MODE_RW: u16 = 0o600;
+
+    /// Creates a directory named `name` in `parent` (or the debugfs root if
+    /// `NULL`).
+    fn new(name: &CStr, parent: *mut dentry) -> Self {
+        // SAFETY: Arguments are valid for API. Errors are returned as error
+        // pointers, which are accepted by the other debugfs functions.
+        let dentry = unsafe { bindings::debugfs_create_dir(name.as_char_ptr(), parent) };
+        Self { dentry }
+    }
+
+    /// Creates a read-only file named `name` showing `data` with `T`.
+    ///
+    /// `data` must be a valid `T::Data` which outlives the directory.
+    fn show_file<T: Show>(&self, name: &CStr, data: *mut T::Data) {
+        // SAFETY: Arguments are valid for API. The file is removed along with
+        // the directory, before `data` is dropped.
+        unsafe {
+            bindings::debugfs_create_file_full(
+                name.as_char_ptr(),
+                Self::MODE_RO,
+                self.dentry,
+                data.cast(),
+                ::core::ptr::null(),
+                &ShowFile::<T>::FOPS,
+            )
+        };
+    }
+
+    /// Creates the raw BAR0 register interface files for `data`.
+    ///
+    /// `data` must be valid and outlive the directory.
+    fn reg_files(&self, data: *mut DeviceData) {
+        // SAFETY: `data` is valid, so the pointer to the field is valid.
+        let addr = unsafe { (*data).reg_addr.as_ptr() };
+        // SAFETY: Arguments are valid for API. The file is removed along with
+        // the directory, before `data` is dropped.
+        unsafe { bindings::debugfs_create_x32(c"reg_addr".as_ptr().cast(), Self::MODE_RW, self.dentry, addr) };
+        // SAFETY: Arguments are valid for API. The file is removed along with
+        // the directory, before `data` is dropped.
+        unsafe {
+            bindings::debugfs_create_file_full(
+                c"reg_data".as_ptr().cast(),
+                Self::MODE_RW,
+                self.dentry,
+                data.cast(),
+                ::core::ptr::null(),
+                &REG_DATA_FOPS,
+            )
+        };
+    }
+}
+
+/// The module of the file operations.
+///
+/// debugfs calls into the operations (e.g., `release`) of files which are
+/// still open after they are removed, so it holds a reference to the module
+/// while they are open.
+#[cfg(all(CONFIG_DEBUG_FS, MODULE))]
+const OWNER: *mut bindings::module = &raw mut bindings::__this_module;
+
+/// The module of the file operations (none, since the driver is built in).
+#[cfg(all(CONFIG_DEBUG_FS, not(MODULE)))]
+const OWNER: *mut bindings::module = ::core::ptr::null_mut();
+
+/// The contents of a `seq_file` backed debugfs file.
+#[cfg(CONFIG_DEBUG_FS)]
+trait Show {
+    /// The type of the data shown by the file.
+    type Data;
+
+    /// Writes the contents of the file for `data` to `m`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if the contents cannot be read, which fails the read of the
+    ///   file.
+    fn show(m: &SeqFile, data: &Self::Data) -> Result;
+}
+
+/// The file operations for a [`Show`] implementation.
+#[cfg(CONFIG_DEBUG_FS)]
+struct ShowFile<T>(PhantomData<T>);
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl<T: Show> ShowFile<T> {
+    /// The file operations.
+    const FOPS: file_operations = file_operations {
+        owner: OWNER,
+        open: Some(Self::open),
+        read: Some(bindings::seq_read),
+        llseek: Some(bindings::seq_lseek),
+        release: Some(bindings::single_release),
+        // SAFETY: All-zeroes is a valid bit pattern for the remaining fields.
+        ..unsafe { MaybeUninit::zeroed().assume_init() }
+    };
+
+    /// Opens the file as a single-record `seq_file`.
+    extern "C" fn open(inode: *mut inode, file: *mut file) -> c_int {
+        // SAFETY: `inode` is valid for the duration of the callback.
+        let data = unsafe { (*inode).i_private };
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        unsafe { bindings::single_open(file, Some(Self::show), data) }
+    }
+
+    /// Shows the contents of the file.
+    extern "C" fn show(m: *mut seq_file, _v: *mut c_void) -> c_int {
+        // SAFETY: `m` is valid for the duration of the callback.
+        let data = unsafe { (*m).private };
+        // SAFETY: `data` is the `T::Data` the file was created with, which
+        // outlives the file.
+        let data = unsafe { &*data.cast::<T::Data>() };
+        // SAFETY: `m` is valid for the duration of the callback.
+        let m = unsafe { SeqFile::from_raw(m) };
+        match T::show(m, data) {
+            Ok(()) => 0,
+            Err(err) => err.to_errno(),
+        }
+    }
+}
+
+/// Shows the misc device ordinal map as `ordinal name` lines.
+#[cfg(CONFIG_DEBUG_FS)]
+struct ShowOrdinals;
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Show for ShowOrdinals {
+    type Data = ();
+
+    fn show(m: &SeqFile, _data: &()) -> Result {
+        TtMisc::for_each_ordinal(|ordinal, name| seq_print!(m, "{ordinal} {name}\n"));
+        Ok(())
+    }
+}
+
+/// Shows the cluster descriptor.
+#[cfg(CONFIG_DEBUG_FS)]
+struct ShowCluster;
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Show for ShowCluster {
+    type Data = ();
+
+    fn show(m: &SeqFile, _data: &()) -> Result {
+        let cluster = Cluster::collect()?;
+        seq_print!(m, "{cluster}");
+        Ok(())
+    }
+}
+
+/// Shows the state of a chip.
+#[cfg(CONFIG_DEBUG_FS)]
+struct ShowState;
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Show for ShowState {
+    type Data = DeviceData;
+
+    fn show(m: &SeqFile, data: &DeviceData) -> Result {
+        let chip = &data.chip;
+        seq_print!(m, "name: {}\n", chip.hw().name);
+        seq_print!(m, "identify_only: {}\n", chip.hw().is_identify_only());
+        seq_print!(m, "emulated: {}\n", chip.is_emulated());
+        seq_print!(m, "failed: {}\n", chip.is_failed());
+        seq_print!(m, "hung: {}\n", chip.is_hung());
+        seq_print!(m, "shut_down: {}\n", chip.is_shut_down());
+        seq_print!(m, "state_generation: {}\n", chip.generation());
+        seq_print!(m, "busy_files: {}\n", chip.busy_files());
+        Ok(())
+    }
+}
+
+/// Shows the open files of a chip as `id pid mode` lines.
+#[cfg(CONFIG_DEBUG_FS)]
+struct ShowFiles;
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Show for ShowFiles {
+    type Data = DeviceData;
+
+    fn show(m: &SeqFile, data: &DeviceData) -> Result {
+        data.chip.for_each_file(|entry| {
+            seq_print!(m, "{} {} {:?}\n", entry.id, entry.pid, entry.mode);
+        });
+        Ok(())
+    }
+}
+
+/// Shows the statistics of messages to the ARC firmware of a chip.
+#[cfg(CONFIG_DEBUG_FS)]
+struct ShowArcStats;
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Show for ShowArcStats {
+    type Data = DeviceData;
+
+    fn show(m: &SeqFile, data: &DeviceData) -> Result {
+        let stats = data.chip.arc_stats();
+        seq_print!(m, "acked: {}\n", stats.acked.load(Relaxed));
+        seq_print!(m, "timed_out: {}\n", stats.timed_out.load(Relaxed));
+        seq_print!(m, "failed: {}\n", stats.failed.load(Relaxed));
+        Ok(())
+    }
+}
+
+/// Shows the NOC grid of a chip as a map in each coordinate system (NOC0,
+/// NOC1 and, if the chip translates coordinates, translated), one line per row
+/// from Y = 0.
+///
+/// Each core is shown by its kind: `T` (Tensix), `D` (DRAM), `E` (Ethernet),
+/// `P` (PCIe) or `A` (ARC), in lowercase if disabled by harvesting. Other
+/// locations are shown as `.`. If the chip does not report its harvesting,
+/// all cores are shown as enabled.
+#[cfg(CONFIG_DEBUG_FS)]
+struct ShowNocGrid;
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Show for ShowNocGrid {
+    type Data = DeviceData;
+
+    fn show(m: &SeqFile, data: &DeviceData) -> Result {
+        let grid = data.chip.hw().grid;
+        let harvesting = match data.chip.harvesting() {
+            Ok(harvesting) => Some(harvesting),
+            Err(err) if err == EOPNOTSUPP => None,
+            Err(err) => return Err(err),
+        };
+        let maps = [
+            ("noc0", Coords::Noc0),
+            ("noc1", Coords::Noc1),
+            ("translated", Coords::Translated),
+        ];
+        for (name, coords) in maps {
+            if coords == Coords::Translated && grid.translation.is_none() {
+                continue;
+            }
+            seq_print!(m, "{name}:\n");
+            let (width, height) = grid.extent(coords);
+            for y in 0 .. height {
+                for x in 0 .. width {
+                    let core = grid.to_noc0(coords, x, y).and_then(|(x, y)| grid.core_at(x, y));
+                    let symbol = core.map_or('.', |core| {
+                        let symbol = match core.kind {
+                            CoreKind::Tensix => 'T',
+                            CoreKind::Dram => 'D',
+                            CoreKind::Eth => 'E',
+                            CoreKind::Pcie => 'P',
+                            CoreKind::Arc => 'A',
+                        };
+                        let disabled = harvesting
+                            .as_ref()
+                            .is_some_and(|harvesting| grid.is_disabled(&core, harvesting));
+                        if disabled { symbol.to_ascii_lowercase() } else { symbol }
+                    });
+                    seq_print!(m, "{symbol}");
+                }
+                seq_print!(m, "\n");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Shows the allocation of the TLB windows of a chip as `first-last size
+/// owner` lines (sizes in KiB), followed by the target of the window reserved
+/// for the driver as `kernel_target: x y addr` (NOC0).
+#[cfg(CONFIG_DEBUG_FS)]
+struct ShowTlbs;
+
+#[cfg(CONFIG_DEBUG_FS)]
+impl Show for ShowTlbs {
+    type Data = DeviceData;
+
+    fn show(m: &SeqFile, data: &DeviceData) -> Result {
+        let (ranges, (x, y, addr)) = data.chip.tlb_allocation()?;
+        for range in ranges {
+            let size_kib = range.size >> 10;
+            seq_print!(m, "{}-{} {size_kib} {:?}\n", range.first, range.last, range.owner);
+        }
+        seq_print!(m, "kernel_target: {x} {y} {addr:#x}\n");
+        Ok(())
+    }
+}
+
+/// The file operations for `reg_data`.
+#[cfg(CONFIG_DEBUG_FS)]
+const REG_DATA_FOPS: file_operations = {
+    /// Opens the file as a simple attribute.
+    extern "C" fn open(inode: *mut inode, file: *mut file) -> c_int {
+        let fmt = ::kernel::c_str!("0x%08llx\n").as_char_ptr();
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        unsafe { bindings::simple_attr_open(inode, file, Some(get), Some(set), fmt) }
+    }
+
+    /// Reads the register at `reg_addr`.
+    extern "C" fn get(data: *mut c_void, val: *mut u64) -> c_int {
+        // SAFETY: `data` is the `DeviceData` the file was created with.
+        let data = unsafe { &*data.cast::<DeviceData>() };
+        let Ok(offset) = usize::try_from(data.reg_addr.load(Relaxed)) else {
+            return EINVAL.to_errno();
+        };
+        match data.chip.read_reg(offset) {
+            Ok(value) => {
+                // SAFETY: `val` is valid for writes for the duration of the callback.
+                unsafe { val.write(u64::from(value)) };
+                0
+            },
+            Err(err) => err.to_errno(),
+        }
+    }
+
+    /// Writes the register at `reg_addr`.
+    extern "C" fn set(data: *mut c_void, val: u64) -> c_int {
+        // SAFETY: `data` is the `DeviceData` the file was created with.
+        let data = unsafe { &*data.cast::<DeviceData>() };
+        let (Ok(offset), Ok(value)) = (usize::try_from(data.reg_addr.load(Relaxed)), u32::try_from(val)) else {
+            return EINVAL.to_errno();
+        };
+        match data.chip.write_reg(offset, value) {
+            Ok(()) => 0,
+            Err(err) => err.to_errno(),
+        }
+    }
+
+    file_operations {
+        owner: OWNER,
+        open: Some(open),
+        read: Some(bindings::simple_attr_read),
+        write: Some(bindings::simple_attr_write),
+        release: Some(bindings::simple_attr_release),
+        llseek: Some(bindings::generic_file_llseek),
+        // SAFETY: All-zeroes is a valid bit pattern for the remaining fields.
+        ..unsafe { MaybeUninit::zeroed().assume_init() }
+    }
+};
+
+#[cfg(CONFIG_DEBUG_FS)]
+::kernel::sync::global_lock! {
+    // SAFETY: Initialized in `TtDebugfs::new` before first use.
+    unsafe(uninit) static ROOT: Mutex<Option<Dir>> = None;
+}
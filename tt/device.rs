@@ -1,7 +1,25 @@
 // SPDX-License-Identifier: GPL-2.0
 
+/// Definitions related to the per-chip state.
+pub(crate) mod chip;
+
+/// Definitions related to the coredumps of failed chips.
+pub(crate) mod coredump;
+
+/// Definitions related to software-emulated devices.
+pub(crate) mod emu;
+
+/// Definitions related to the device state change events of open files.
+pub(crate) mod event;
+
+/// Definitions related to interrupts and their delivery to userspace.
+pub(crate) mod irq;
+
 /// Definitions related to the hardware misc devices.
 pub(crate) mod misc;
 
 /// Definitions related to the hardware PCI devices.
 pub(crate) mod pci;
+
+/// Definitions related to the heartbeat watchdog of the chips.
+pub(crate) mod watchdog;
@@ -0,0 +1,1312 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::kernel::{
+    bindings,
+    device::{Core, Device},
+    devres::Devres,
+    firmware::Firmware,
+    pci::{self, ChannelState, ErsResult},
+    prelude::*,
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{Atomic, Relaxed},
+    },
+    time::{Delta, delay::fsleep},
+};
+
+use crate::tt::{
+    device::{
+        coredump::{CoreDump, Reason},
+        event::{EventKind, EventQueue},
+        irq::IrqEvents,
+        misc::OpenMode,
+    },
+    hw::{
+        Arch,
+        BAR0_SIZE,
+        Bar0,
+        Board,
+        HwConfig,
+        RegIo,
+        arc::{self, ArcMsg},
+        emu::EmuRegs,
+        flash::{self, FlashLayout},
+        fw::{self, FwImage, FwTarget},
+        power::PowerBounds,
+        telemetry::{Telemetry, TelemetryLayout},
+        wormhole::tlb::TlbRange,
+    },
+    uapi::{Harvesting, PowerInfo, PowerLimits, Topology},
+};
+
+/// Per-chip state shared by the PCI device and its users.
+#[pin_data]
+pub(crate) struct TtChip {
+    /// The hardware configuration matched at probe.
+    hw: HwConfig,
+    /// The board matched by PCI subsystem device ID, if known.
+    board: Option<&'static Board>,
+    /// The ordinal of the misc device (recorded by trace events).
+    ordinal: usize,
+    /// The access to the registers of the chip.
+    regs: Regs,
+    /// The log of recent messages to the ARC firmware (whose lock serializes
+    /// the messages).
+    #[pin]
+    arc_log: Mutex<ArcLog>,
+    /// Serializes uses of the TLB window reserved for the driver.
+    #[pin]
+    tlb_lock: Mutex<()>,
+    /// Serializes transfers through the SPI buffer of the firmware.
+    #[pin]
+    flash_lock: Mutex<()>,
+    /// The power and clock limits set by the driver (the defaults of the
+    /// board until set, zero if the board is not known).
+    #[pin]
+    power: Mutex<PowerLimits>,
+    /// The number of open files which hinted that they are busy (whose lock
+    /// serializes the changes of power state).
+    #[pin]
+    busy_files: Mutex<u32>,
+    /// The number of times the device state was lost (e.g., across a suspend).
+    generation: Atomic<u32>,
+    /// Whether the chip was put into a safe state for the system going down.
+    shut_down: Atomic<u32>,
+    /// Whether the device was unbound (set under the lock of `files`, see
+    /// [`TtChip::remove`]).
+    removed: Atomic<u32>,
+    /// Whether the chip has failed (after a PCI error, until it recovers).
+    failed: Atomic<u32>,
+    /// Whether the firmware of the chip is hung (after its heartbeat stopped,
+    /// until the chip is reset).
+    hung: Atomic<u32>,
+    /// Whether a coredump was captured after a firmware fault (until the chip
+    /// is reset or resumed), so that a storm of faults captures one.
+    fault_dumped: Atomic<u32>,
+    /// The counts of PCI error recovery events.
+    aer: AerCounts,
+    /// The statistics of messages to the ARC firmware.
+    arc_stats: ArcStats,
+    /// The versions of the loaded firmware images (0 if not loaded), by image
+    /// of [`HwConfig::firmware`].
+    fw_versions: [Atomic<u32>; fw::MAX_IMAGES],
+    /// The BAR0 offset of the telemetry table, as reported by the firmware at
+    /// probe or after the last reset (see [`TtChip::read_firmware_info`]).
+    #[pin]
+    telemetry_at: Mutex<Result<usize>>,
+    /// The harvesting of the chip, as reported by the firmware at probe or
+    /// after the last reset (see [`TtChip::read_firmware_info`]).
+    #[pin]
+    harvesting: Mutex<Result<Harvesting>>,
+    /// The files currently open for the chip.
+    #[pin]
+    files: Mutex<KVec<FileEntry>>,
+    /// The identifier for the next opened file.
+    next_file_id: Atomic<u64>,
+    /// The eventfds bound to interrupt sources.
+    #[pin]
+    events: IrqEvents,
+}
+
+/// The access to the registers of a chip.
+enum Regs {
+    /// No access (in identify-only mode).
+    None,
+    /// The mapping of BAR0.
+    Bar(Devres<Bar0>),
+    /// The register model of an emulated chip.
+    Emulated(Arc<EmuRegs>),
+}
+
+/// The registers of a chip, tracing the TLB windows pointed at its cores
+/// (`tt_tlb_configure`).
+struct ChipRegs<'regs> {
+    /// The registers.
+    regs: &'regs dyn RegIo,
+    /// The ordinal of the misc device of the chip.
+    ordinal: usize,
+}
+
+impl RegIo for ChipRegs<'_> {
+    fn read_reg(&self, offset: usize) -> Result<u32> {
+        self.regs.read_reg(offset)
+    }
+
+    fn write_reg(&self, offset: usize, value: u32) -> Result {
+        self.regs.write_reg(offset, value)
+    }
+
+    fn tlb_configured(&self, tlb: usize, x: u8, y: u8, addr: u64) {
+        crate::tt::trace::tlb_configure(self.ordinal, tlb, x, y, addr);
+    }
+}
+
+/// An open file of a chip.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct FileEntry {
+    /// The identifier of the file (unique per chip).
+    pub id: u64,
+    /// The PID of the task which opened the file.
+    pub pid: i32,
+    /// The access mode the file was opened with.
+    pub mode: OpenMode,
+    /// The event queue of the file.
+    queue: Arc<EventQueue>,
+}
+
+/// The statistics of messages to the ARC firmware of a chip.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct ArcStats {
+    /// Messages which were acknowledged.
+    pub acked: Atomic<u32>,
+    /// Messages which timed out.
+    pub timed_out: Atomic<u32>,
+    /// Messages which failed otherwise (e.g., while the ARC was busy).
+    pub failed: Atomic<u32>,
+}
+
+impl ArcStats {
+    /// Creates zeroed [`ArcStats`].
+    const fn new() -> Self {
+        Self {
+            acked: Atomic::new(0u32),
+            timed_out: Atomic::new(0u32),
+            failed: Atomic::new(0u32),
+        }
+    }
+}
+
+/// A message to the ARC firmware, as recorded in an [`ArcLog`].
+#[derive(Clone, Copy)]
+pub(crate) struct ArcLogEntry {
+    /// The time the message was sent (`CLOCK_MONOTONIC`) in nanoseconds.
+    pub(crate) timestamp_ns: u64,
+    /// The message code.
+    pub(crate) code: u8,
+    /// The argument of the message.
+    pub(crate) arg: u32,
+    /// The reply to the message, or the error sending it.
+    pub(crate) reply: Result<u32>,
+}
+
+/// The ring of the most recent messages to the ARC firmware of a chip.
+#[derive(Clone)]
+pub(crate) struct ArcLog {
+    /// The entries, the oldest at `next` once the ring is full.
+    entries: [Option<ArcLogEntry>; ArcLog::CAPACITY],
+    /// The index of the next entry to write.
+    next: usize,
+}
+
+impl ArcLog {
+    /// The number of messages kept.
+    const CAPACITY: usize = 32;
+
+    /// Creates an empty [`ArcLog`].
+    const fn new() -> Self {
+        Self {
+            entries: [None; Self::CAPACITY],
+            next: 0,
+        }
+    }
+
+    /// Records `entry`, replacing the oldest entry if the ring is full.
+    fn record(&mut self, entry: ArcLogEntry) {
+        self.entries[self.next] = Some(entry);
+        self.next = (self.next + 1) % Self::CAPACITY;
+    }
+
+    /// Returns the entries, the oldest first.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &ArcLogEntry> + '_ {
+        let (newer, older) = self.entries.split_at(self.next);
+        older.iter().chain(newer).flatten()
+    }
+}
+
+/// The counts of PCI error recovery events of a chip.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct AerCounts {
+    /// Errors reported while the channel remained functional.
+    pub normal: Atomic<u32>,
+    /// Errors which froze the channel.
+    pub frozen: Atomic<u32>,
+    /// Errors which disconnected the device permanently.
+    pub perm_failure: Atomic<u32>,
+    /// Slot resets performed for recovery.
+    pub slot_reset: Atomic<u32>,
+    /// Recoveries of a failed chip.
+    pub recovered: Atomic<u32>,
+}
+
+impl AerCounts {
+    /// Creates zeroed [`AerCounts`].
+    const fn new() -> Self {
+        Self {
+            normal: Atomic::new(0u32),
+            frozen: Atomic::new(0u32),
+            perm_failure: Atomic::new(0u32),
+            slot_reset: Atomic::new(0u32),
+            recovered: Atomic::new(0u32),
+        }
+    }
+}
+
+impl TtChip {
+    /// The time given to the chip to come out of a function reset.
+    const RESET_DELAY: Delta = Delta::from_millis(500);
+
+    /// Creates the state for the chip of `dev` on `board` with misc device
+    /// `ordinal`, mapping BAR0 unless `hw` is identify-only.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if mapping BAR0 fails.
+    /// * Errors if allocating the state fails.
+    pub(crate) fn new(
+        dev: &pci::Device<Core>,
+        hw: HwConfig,
+        board: Option<&'static Board>,
+        ordinal: usize,
+    ) -> Result<Arc<Self>> {
+        let regs = if hw.is_identify_only() {
+            Regs::None
+        } else {
+            Regs::Bar(dev.iomap_region_sized::<BAR0_SIZE>(0, ::kernel::c_str!("tt_core"))?)
+        };
+        Self::with_regs(hw, board, ordinal, regs)
+    }
+
+    /// Creates the state for an emulated chip with misc device `ordinal`,
+    /// backed by a register model instead of a PCI device.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the model or the state fails.
+    pub(crate) fn emulated(hw: HwConfig, ordinal: usize) -> Result<Arc<Self>> {
+        let regs = Regs::Emulated(EmuRegs::new()?);
+        Self::with_regs(hw, None, ordinal, regs)
+    }
+
+    /// Creates the state for a chip on `board` with misc device `ordinal` and
+    /// `regs`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the state fails.
+    fn with_regs(hw: HwConfig, board: Option<&'static Board>, ordinal: usize, regs: Regs) -> Result<Arc<Self>> {
+        let init = try_pin_init!(Self {
+            hw,
+            board,
+            ordinal,
+            regs,
+            arc_log <- ::kernel::new_mutex!(ArcLog::new()),
+            tlb_lock <- ::kernel::new_mutex!(()),
+            flash_lock <- ::kernel::new_mutex!(()),
+            power <- ::kernel::new_mutex!(board.map(|board| board.power.defaults()).unwrap_or_default()),
+            busy_files <- ::kernel::new_mutex!(0u32),
+            generation: Atomic::new(0u32),
+            shut_down: Atomic::new(0u32),
+            removed: Atomic::new(0u32),
+            failed: Atomic::new(0u32),
+            hung: Atomic::new(0u32),
+            fault_dumped: Atomic::new(0u32),
+            aer: AerCounts::new(),
+            arc_stats: ArcStats::new(),
+            fw_versions: ::core::array::from_fn(|_index| Atomic::new(0u32)),
+            telemetry_at <- ::kernel::new_mutex!(Err(EAGAIN)),
+            harvesting <- ::kernel::new_mutex!(Err(EAGAIN)),
+            files <- ::kernel::new_mutex!(KVec::new()),
+            next_file_id: Atomic::new(0u64),
+            events <- IrqEvents::new(),
+        });
+        Arc::pin_init(init, GFP_KERNEL)
+    }
+
+    /// Returns the ordinal of the misc device.
+    pub(crate) const fn ordinal(&self) -> usize {
+        self.ordinal
+    }
+
+    /// Returns the eventfds bound to interrupt sources.
+    pub(crate) const fn events(&self) -> &IrqEvents {
+        &self.events
+    }
+
+    /// Returns the number of times the device state was lost.
+    pub(crate) fn generation(&self) -> u32 {
+        self.generation.load(Relaxed)
+    }
+
+    /// Returns the counts of PCI error recovery events.
+    pub(crate) const fn aer(&self) -> &AerCounts {
+        &self.aer
+    }
+
+    /// Returns the statistics of messages to the ARC firmware.
+    pub(crate) const fn arc_stats(&self) -> &ArcStats {
+        &self.arc_stats
+    }
+
+    /// Returns the hardware configuration.
+    pub(crate) const fn hw(&self) -> &HwConfig {
+        &self.hw
+    }
+
+    /// Returns the board matched by PCI subsystem device ID, if known.
+    pub(crate) const fn board(&self) -> Option<&'static Board> {
+        self.board
+    }
+
+    /// Returns `true` if the chip has failed and not yet recovered.
+    pub(crate) fn is_failed(&self) -> bool {
+        self.failed.load(Relaxed) != 0
+    }
+
+    /// Returns `true` if the firmware of the chip was found hung and the chip
+    /// has not been reset since.
+    pub(crate) fn is_hung(&self) -> bool {
+        self.hung.load(Relaxed) != 0
+    }
+
+    /// Returns `true` if the chip was put into a safe state for the system
+    /// going down.
+    pub(crate) fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Relaxed) != 0
+    }
+
+    /// Records a newly opened file (with its event `queue`) and returns its
+    /// identifier.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENODEV` if the device was unbound (see
+    ///   [`TtChip::remove`]).
+    /// * Errors if allocating the entry fails.
+    pub(crate) fn add_file(&self, mode: OpenMode, queue: &Arc<EventQueue>) -> Result<u64> {
+        let mut files = self.files.lock();
+        if self.removed.load(Relaxed) != 0 {
+            return Err(ENODEV);
+        }
+        let id = self.next_file_id.fetch_add(1, Relaxed);
+        let pid = ::kernel::current!().pid();
+        let queue = Arc::clone(queue);
+        files.push(FileEntry { id, pid, mode, queue }, GFP_KERNEL)?;
+        Ok(id)
+    }
+
+    /// Marks the device as unbound and queues [`EventKind::Removed`] for every
+    /// open file.
+    ///
+    /// The device node may still be opened until its registration is dropped,
+    /// so later opens fail with `ENODEV` rather than miss the event.
+    pub(crate) fn remove(&self) {
+        let generation = self.generation();
+        let files = self.files.lock();
+        self.removed.store(1, Relaxed);
+        for entry in files.iter() {
+            entry.queue.push(EventKind::Removed, generation, 0);
+        }
+    }
+
+    /// Queues an event of `kind` with `data` for every open file.
+    pub(crate) fn post(&self, kind: EventKind, data: u64) {
+        let generation = self.generation();
+        for entry in self.files.lock().iter() {
+            entry.queue.push(kind, generation, data);
+        }
+    }
+
+    /// Forgets the closed file `id`.
+    pub(crate) fn remove_file(&self, id: u64) {
+        self.files.lock().retain(|entry| entry.id != id);
+    }
+
+    /// Calls `f` for each file currently open for the chip.
+    pub(crate) fn for_each_file<F: FnMut(&FileEntry)>(&self, mut f: F) {
+        for entry in self.files.lock().iter() {
+            f(entry);
+        }
+    }
+
+    /// Returns `true` if the chip is emulated (see [`EmuRegs`]).
+    pub(crate) const fn is_emulated(&self) -> bool {
+        matches!(self.regs, Regs::Emulated(_))
+    }
+
+    /// Calls `f` with the access to the registers of the chip (see
+    /// [`ChipRegs`]).
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENODEV` if BAR0 is not mapped.
+    /// * Errors with `ENXIO` if the device has been unbound.
+    /// * Errors if `f` fails.
+    fn access<T, F: FnOnce(&dyn RegIo) -> Result<T>>(&self, f: F) -> Result<T> {
+        let ordinal = self.ordinal;
+        match &self.regs {
+            Regs::None => Err(ENODEV),
+            Regs::Bar(bar) => {
+                let bar = bar.try_access().ok_or(ENXIO)?;
+                f(&ChipRegs { regs: &*bar, ordinal })
+            },
+            Regs::Emulated(regs) => f(&ChipRegs { regs: &**regs, ordinal }),
+        }
+    }
+
+    /// Reads the BAR0 register at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENODEV` if BAR0 is not mapped.
+    /// * Errors with `ENXIO` if the device has been unbound.
+    /// * Errors if `offset` is out of bounds or misaligned.
+    pub(crate) fn read_reg(&self, offset: usize) -> Result<u32> {
+        self.access(|regs| regs.read_reg(offset))
+    }
+
+    /// Writes `value` to the BAR0 register at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENODEV` if BAR0 is not mapped.
+    /// * Errors with `ENXIO` if the device has been unbound.
+    /// * Errors if `offset` is out of bounds or misaligned.
+    pub(crate) fn write_reg(&self, offset: usize, value: u32) -> Result {
+        self.access(|regs| regs.write_reg(offset, value))
+    }
+
+    /// Checks that the chip has neither failed nor hung.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EIO` if the chip has failed or hung and not yet
+    ///   recovered.
+    pub(crate) fn check_alive(&self) -> Result {
+        if self.is_failed() || self.is_hung() {
+            return Err(EIO);
+        }
+        Ok(())
+    }
+
+    /// Sends `msg` to the ARC firmware and returns the reply (see
+    /// [`arc::send_scratch`]).
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENODEV` if BAR0 is not mapped.
+    /// * Errors with `ENXIO` if the device has been unbound.
+    /// * Errors with `EOPNOTSUPP` if the architecture is not supported.
+    /// * Errors if sending the message fails.
+    pub(crate) fn arc_msg(&self, msg: ArcMsg) -> Result<u32> {
+        self.access(|regs| {
+            let mut log = self.arc_log.lock();
+            // SAFETY: FFI call without safety requirements.
+            let now = unsafe { bindings::ktime_get() };
+            let code = u32::from(msg.code());
+            if !self.hw.has_arc_msg() {
+                return Err(EOPNOTSUPP);
+            }
+            crate::tt::trace::arc_msg_send(self.ordinal, code, msg.arg());
+            let res = arc::send_scratch(regs, msg);
+            crate::tt::trace::arc_msg_reply(self.ordinal, code, &res);
+            match res {
+                Ok(_) => self.arc_stats.acked.add(1, Relaxed),
+                Err(err) if err == ETIMEDOUT => self.arc_stats.timed_out.add(1, Relaxed),
+                Err(_) => self.arc_stats.failed.add(1, Relaxed),
+            }
+            log.record(ArcLogEntry {
+                timestamp_ns: u64::try_from(now).unwrap_or(0),
+                code: msg.code(),
+                arg: msg.arg(),
+                reply: res,
+            });
+            res
+        })
+    }
+
+    /// Returns a copy of the log of recent messages to the ARC firmware.
+    pub(crate) fn arc_log(&self) -> ArcLog {
+        self.arc_log.lock().clone()
+    }
+
+    /// Reads the location of the telemetry table and the harvesting of the
+    /// chip from the ARC firmware.
+    ///
+    /// Both are kept until the next call (after a reset or resume), so that
+    /// reading them (e.g., through sysfs) does not message the firmware.
+    pub(crate) fn read_firmware_info(&self) {
+        let telemetry_at = self
+            .hw
+            .telemetry
+            .ok_or(EOPNOTSUPP)
+            .and_then(|layout| self.telemetry_offset(layout));
+        *self.telemetry_at.lock() = telemetry_at;
+        *self.harvesting.lock() = self.read_harvesting();
+    }
+
+    /// Reads a snapshot of the telemetry table of the ARC firmware, at the
+    /// location read by [`TtChip::read_firmware_info`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the layout of the table is not known.
+    /// * Errors with `EIO` if the firmware reported a table outside the CSM.
+    /// * Errors with `EAGAIN` if the location has not been read yet.
+    /// * Errors if messaging the ARC firmware failed, or reading the table
+    ///   fails.
+    pub(crate) fn telemetry(&self) -> Result<Telemetry> {
+        let layout = self.hw.telemetry.ok_or(EOPNOTSUPP)?;
+        let offset = (*self.telemetry_at.lock())?;
+        self.access(|regs| Telemetry::read(regs, offset, layout))
+    }
+
+    /// Returns the BAR0 offset of the heartbeat word of the telemetry table
+    /// (see [`TelemetryLayout::heartbeat`]).
+    ///
+    /// The firmware keeps the table in place while it runs, so the word may be
+    /// read repeatedly (with [`TtChip::read_reg`]) until the chip is reset.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the table has no known heartbeat.
+    /// * Errors with `EIO` if the firmware reports a table outside the CSM.
+    /// * Errors if messaging the ARC firmware fails.
+    pub(crate) fn heartbeat_offset(&self) -> Result<usize> {
+        let layout = self.hw.telemetry.ok_or(EOPNOTSUPP)?;
+        let index = layout.heartbeat.ok_or(EOPNOTSUPP)?;
+        Ok(self.telemetry_offset(layout)? + index * 4)
+    }
+
+    /// Returns the BAR0 offset of the throttler word of the telemetry table
+    /// (see [`TelemetryLayout::throttler`]), which may be read repeatedly as
+    /// for [`TtChip::heartbeat_offset`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the table has no known throttler.
+    /// * Errors with `EIO` if the firmware reports a table outside the CSM.
+    /// * Errors if messaging the ARC firmware fails.
+    pub(crate) fn throttler_offset(&self) -> Result<usize> {
+        let layout = self.hw.telemetry.ok_or(EOPNOTSUPP)?;
+        let index = layout.throttler.ok_or(EOPNOTSUPP)?;
+        Ok(self.telemetry_offset(layout)? + index * 4)
+    }
+
+    /// Returns the BAR0 offset of the telemetry table of `layout`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EIO` if the firmware reports a table outside the CSM.
+    /// * Errors if messaging the ARC firmware fails.
+    fn telemetry_offset(&self, layout: &TelemetryLayout) -> Result<usize> {
+        let addr = self.arc_msg(ArcMsg::GetTelemetry)?;
+        arc::csm_offset(addr, layout.size()?)
+    }
+
+    /// Reads the Ethernet topology of the chip: its coordinates and the state
+    /// and peers of its links.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENODEV` if BAR0 is not mapped.
+    /// * Errors with `ENXIO` if the device has been unbound.
+    /// * Errors with `EOPNOTSUPP` if the architecture is not supported.
+    /// * Errors if reading from the Ethernet cores fails.
+    pub(crate) fn topology(&self) -> Result<Topology> {
+        self.access(|regs| match self.hw.arch {
+            Arch::Wormhole => {
+                let _guard = self.tlb_lock.lock();
+                crate::tt::hw::wormhole::eth::discover(regs)
+            },
+            // NOTE: Grayskull has no Ethernet, and the Blackhole Ethernet
+            // firmware reports its links differently (not supported yet).
+            Arch::Grayskull | Arch::Blackhole | Arch::Unknown => Err(EOPNOTSUPP),
+        })
+    }
+
+    /// Returns the allocation of the TLB windows of the chip and the target of
+    /// the window reserved for the driver (see
+    /// [`crate::tt::hw::wormhole::tlb::kernel_target`]).
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENODEV` if BAR0 is not mapped.
+    /// * Errors with `ENXIO` if the device has been unbound.
+    /// * Errors with `EOPNOTSUPP` if the architecture is not supported.
+    /// * Errors if reading the registers fails.
+    pub(crate) fn tlb_allocation(&self) -> Result<(&'static [TlbRange], (u8, u8, u64))> {
+        self.access(|regs| match self.hw.arch {
+            Arch::Wormhole => {
+                let _guard = self.tlb_lock.lock();
+                let target = crate::tt::hw::wormhole::tlb::kernel_target(regs)?;
+                Ok((&crate::tt::hw::wormhole::tlb::ALLOCATION[..], target))
+            },
+            // NOTE: Only the Wormhole TLB windows are used by the driver so
+            // far.
+            Arch::Grayskull | Arch::Blackhole | Arch::Unknown => Err(EOPNOTSUPP),
+        })
+    }
+
+    /// Returns the harvesting of the chip: the units disabled by its fuses, as
+    /// read by [`TtChip::read_firmware_info`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EAGAIN` if the harvesting has not been read yet.
+    /// * Errors if reading the harvesting failed (see
+    ///   [`TtChip::read_harvesting`]).
+    pub(crate) fn harvesting(&self) -> Result<Harvesting> {
+        *self.harvesting.lock()
+    }
+
+    /// Reads the harvesting of the chip from the ARC firmware.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENODEV` if BAR0 is not mapped.
+    /// * Errors with `ENXIO` if the device has been unbound.
+    /// * Errors with `EOPNOTSUPP` if the architecture is not supported.
+    /// * Errors if messaging the ARC firmware fails.
+    fn read_harvesting(&self) -> Result<Harvesting> {
+        match self.hw.arch {
+            Arch::Grayskull | Arch::Wormhole => {
+                let raw = self.arc_msg(ArcMsg::GetHarvesting)?;
+                Ok(self.hw.grid.harvesting(raw))
+            },
+            // NOTE: Blackhole reports harvesting through telemetry tables in
+            // ARC memory, which is only reachable through the NOC and not
+            // supported yet.
+            Arch::Blackhole => Err(EOPNOTSUPP),
+            Arch::Unknown => Err(ENODEV),
+        }
+    }
+
+    /// Returns the layout of the SPI flash of the board.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the layout is not known.
+    pub(crate) fn flash_layout(&self) -> Result<&'static FlashLayout> {
+        self.hw.flash.ok_or(EOPNOTSUPP)
+    }
+
+    /// Returns the layout of the SPI flash of the board, if its blocks may be
+    /// transferred (see the `unsafe_flash_access` parameter).
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the layout is not known or transfers are
+    ///   not allowed.
+    fn flash_transfer_layout(&self) -> Result<&'static FlashLayout> {
+        if *crate::module_parameters::unsafe_flash_access.value() == 0 {
+            return Err(EOPNOTSUPP);
+        }
+        self.flash_layout()
+    }
+
+    /// Reads the flash block at `offset` into `block` (of the block size).
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the flash cannot be accessed.
+    /// * Errors with `EIO` if the firmware reports a buffer outside its window
+    ///   or fails the transfer.
+    /// * Errors if messaging the ARC firmware or reading the buffer fails.
+    pub(crate) fn flash_read_block(&self, offset: u32, block: &mut [u8]) -> Result {
+        let layout = self.flash_transfer_layout()?;
+        let _guard = self.flash_lock.lock();
+        let buffer = layout.buffer_offset(self.arc_msg(ArcMsg::GetSpiBuffer)?)?;
+        if self.arc_msg(ArcMsg::SpiRead(offset))? != 0 {
+            return Err(EIO);
+        }
+        self.access(|regs| flash::read_buffer(regs, buffer, block))
+    }
+
+    /// Writes `block` (of the block size) to the flash block at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the flash cannot be accessed.
+    /// * Errors with `EIO` if the firmware reports a buffer outside its window
+    ///   or fails the transfer.
+    /// * Errors if messaging the ARC firmware or writing the buffer fails.
+    pub(crate) fn flash_write_block(&self, offset: u32, block: &[u8]) -> Result {
+        let layout = self.flash_transfer_layout()?;
+        let _guard = self.flash_lock.lock();
+        let buffer = layout.buffer_offset(self.arc_msg(ArcMsg::GetSpiBuffer)?)?;
+        self.access(|regs| flash::write_buffer(regs, buffer, block))?;
+        if self.arc_msg(ArcMsg::SpiWrite(offset))? != 0 {
+            return Err(EIO);
+        }
+        Ok(())
+    }
+
+    /// Returns the bounds of the power and clock limits of the board.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the board is not known or the firmware
+    ///   cannot be messaged (see [`HwConfig::has_arc_msg`]).
+    fn power_bounds(&self) -> Result<&'static PowerBounds> {
+        if !self.hw.has_arc_msg() {
+            return Err(EOPNOTSUPP);
+        }
+        self.board.map(|board| &board.power).ok_or(EOPNOTSUPP)
+    }
+
+    /// Returns the power and clock limits of the chip and the bounds of its
+    /// board.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the limits are not supported (see
+    ///   [`TtChip::power_bounds`]).
+    pub(crate) fn power_info(&self) -> Result<PowerInfo> {
+        let bounds = self.power_bounds()?;
+        Ok(bounds.info(*self.power.lock()))
+    }
+
+    /// Updates the power and clock limits of the chip with `f`, which are kept
+    /// across resets until the driver is unloaded.
+    ///
+    /// If the firmware fails a message, the limits are left as they were,
+    /// though the firmware may have applied some of them.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the limits are not supported (see
+    ///   [`TtChip::power_bounds`]).
+    /// * Errors if the updated limits are not valid (see
+    ///   [`PowerBounds::check`]).
+    /// * Errors if applying the limits fails (see [`TtChip::apply_power`]).
+    pub(crate) fn update_power_limits<F: FnOnce(&mut PowerLimits)>(&self, f: F) -> Result {
+        let bounds = self.power_bounds()?;
+        let mut power = self.power.lock();
+        let mut limits = *power;
+        f(&mut limits);
+        bounds.check(&limits)?;
+        self.apply_power(&limits)?;
+        *power = limits;
+        Ok(())
+    }
+
+    /// Tells the ARC firmware the power and clock `limits`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EIO` if the firmware fails a message.
+    /// * Errors if messaging the ARC firmware fails.
+    fn apply_power(&self, limits: &PowerLimits) -> Result {
+        for msg in [
+            ArcMsg::SetAiclkRange(limits.aiclk_min, limits.aiclk_max),
+            ArcMsg::SetPowerLimit(limits.power_limit),
+            ArcMsg::SetThermalLimit(limits.thermal_limit),
+        ] {
+            if self.arc_msg(msg)? != 0 {
+                return Err(EIO);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the power and clock limits again after the firmware lost them
+    /// (e.g., across a reset), unless they are the defaults of the board.
+    ///
+    /// Failures are logged.
+    fn restore_power(&self) {
+        let Ok(bounds) = self.power_bounds() else {
+            return;
+        };
+        let power = self.power.lock();
+        if *power == bounds.defaults() {
+            return;
+        }
+        if let Err(err) = self.apply_power(&power) {
+            pr_warn!("(power): cannot restore power limits: {err:?}\n");
+        }
+    }
+
+    /// Returns the power and clock limits to the defaults of the board, as
+    /// when the driver is unloaded.
+    ///
+    /// Failures are logged.
+    pub(crate) fn reset_power(&self) {
+        let Ok(bounds) = self.power_bounds() else {
+            return;
+        };
+        let defaults = bounds.defaults();
+        let mut power = self.power.lock();
+        if *power == defaults || self.is_failed() {
+            return;
+        }
+        match self.apply_power(&defaults) {
+            Ok(()) => *power = defaults,
+            Err(err) => pr_warn!("(power): cannot reset power limits: {err:?}\n"),
+        }
+    }
+
+    /// Returns the number of open files which hinted that they are busy.
+    pub(crate) fn busy_files(&self) -> u32 {
+        *self.busy_files.lock()
+    }
+
+    /// Records that an open file became busy (or idle), moving the ARC
+    /// firmware out of its low-power state for the first busy file, and back
+    /// into it once no file is busy (see [`HwConfig::power_states`]).
+    ///
+    /// `file` is the flag of the file, which is changed with the count (under
+    /// its lock), so that the hints of files changing state at once are sent
+    /// in the order of the count. Hinting the state the file is already in
+    /// does nothing. The file is counted even if messaging the firmware
+    /// fails.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the firmware cannot be messaged.
+    /// * Errors with `EINVAL` if the file is idle but no file is counted as
+    ///   busy (which is a bug).
+    /// * Errors with `EIO` if the firmware fails the message.
+    /// * Errors if messaging the ARC firmware fails.
+    pub(crate) fn hint_busy(&self, file: &Atomic<u32>, busy: bool) -> Result {
+        let states = self.hw.power_states.ok_or(EOPNOTSUPP)?;
+        let mut count = self.busy_files.lock();
+        if file.load(Relaxed) == u32::from(busy) {
+            return Ok(());
+        }
+        let msg = if busy {
+            *count += 1;
+            (*count == 1).then_some(states.busy)
+        } else {
+            let Some(idle) = count.checked_sub(1) else {
+                pr_err!("(power): busy file count underflow\n");
+                return Err(EINVAL);
+            };
+            *count = idle;
+            (*count == 0).then_some(states.idle)
+        };
+        file.store(u32::from(busy), Relaxed);
+        if let Some(msg) = msg {
+            if self.arc_msg(msg)? != 0 {
+                return Err(EIO);
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the firmware images of the architecture (see
+    /// [`HwConfig::firmware`]) for the chip of `dev`, if the
+    /// `firmware_autoload` parameter is set.
+    ///
+    /// Images whose file is missing are skipped. Other failures are logged.
+    /// Either way, the image is reported as not loaded.
+    pub(crate) fn load_firmware(&self, dev: &Device) {
+        if self.hw.is_identify_only() || *crate::module_parameters::firmware_autoload.value() == 0 {
+            return;
+        }
+        for (image, version) in self.hw.firmware.iter().zip(&self.fw_versions) {
+            version.store(0, Relaxed);
+            match self.load_image(dev, image) {
+                Ok(loaded) => {
+                    let [major, minor, patch, build] = loaded.to_be_bytes();
+                    pr_info!("(firmware): loaded {} {major}.{minor}.{patch}.{build}\n", image.path);
+                    version.store(loaded, Relaxed);
+                },
+                Err(err) if err == ENOENT => {},
+                Err(err) => pr_warn!("(firmware): cannot load {}: {err:?}\n", image.path),
+            }
+        }
+    }
+
+    /// Loads `image` for the chip of `dev` and returns its version.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENOENT` if the file is missing.
+    /// * Errors with `EOPNOTSUPP` if the target is not supported.
+    /// * Errors if the file is invalid (see [`FwImage::check`]).
+    /// * Errors if reading the harvesting fails (see [`TtChip::harvesting`]).
+    /// * Errors if writing the image fails.
+    fn load_image(&self, dev: &Device, image: &FwImage) -> Result<u32> {
+        let file = Firmware::request_nowarn(image.path, dev)?;
+        let (header, data) = image.check(self.hw.device_id, file.data())?;
+        // Cores disabled by harvesting are skipped, so it must be known.
+        let harvesting = self.harvesting()?;
+        self.access(|regs| match (self.hw.arch, image.target) {
+            (Arch::Wormhole, FwTarget::EthL1) => {
+                let _guard = self.tlb_lock.lock();
+                let addr = u64::from(header.load_addr);
+                crate::tt::hw::wormhole::eth::load(regs, addr, data, harvesting.eth_cores)
+            },
+            // NOTE: Only the Wormhole NOC is reachable (through its TLB
+            // windows) so far.
+            (Arch::Grayskull | Arch::Blackhole | Arch::Unknown, FwTarget::EthL1) => Err(EOPNOTSUPP),
+        })?;
+        Ok(header.version)
+    }
+
+    /// Returns the firmware images of the architecture with the versions
+    /// loaded (0 if not loaded).
+    pub(crate) fn firmware_versions(&self) -> impl Iterator<Item = (&'static FwImage, u32)> + '_ {
+        let images = self.hw.firmware;
+        images
+            .iter()
+            .zip(&self.fw_versions)
+            .map(|(image, version)| (image, version.load(Relaxed)))
+    }
+
+    /// Prepares the chip for a system sleep state.
+    ///
+    /// Asks the ARC firmware to enter its low-power state (unless it cannot be
+    /// messaged, see [`HwConfig::has_arc_msg`], which is logged), quiesces DMA
+    /// by disabling bus mastering, and saves the config space.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if saving the config space fails.
+    pub(crate) fn suspend(&self, dev: &pci::Device<Core>) -> Result {
+        self.post(EventKind::Suspend, 0);
+        if self.hw.is_identify_only() {
+            return Ok(());
+        }
+
+        if !self.hw.has_arc_msg() {
+            pr_info!(
+                "(suspend): cannot message {} firmware to enter low-power state\n",
+                self.hw.name
+            );
+        } else if let Err(err) = self.arc_msg(ArcMsg::AState3) {
+            pr_warn!("(suspend): firmware did not enter low-power state: {err:?}\n");
+        }
+
+        Self::quiesce(dev);
+        Self::save_state(dev)
+    }
+
+    /// Restores the chip after a system sleep state.
+    ///
+    /// Restores the config space, re-enables bus mastering, returns the ARC
+    /// firmware to its full power state (if it can be messaged), applies the
+    /// power limits and loads the firmware images again, and records that
+    /// device state was lost.
+    pub(crate) fn resume(&self, dev: &pci::Device<Core>) {
+        if self.hw.is_identify_only() {
+            self.post(EventKind::Resume, 0);
+            return;
+        }
+
+        let pdev = crate::tt::device::pci::raw_pci_dev(dev);
+        // SAFETY: `pdev` is valid for the duration of the call.
+        unsafe { bindings::pci_restore_state(pdev) };
+        dev.set_master();
+
+        if self.hw.has_arc_msg() {
+            if let Err(err) = self.arc_msg(ArcMsg::AState0) {
+                pr_warn!("(resume): firmware did not leave low-power state: {err:?}\n");
+            }
+        }
+        self.read_firmware_info();
+        self.restore_power();
+        let core: &Device<Core> = dev.as_ref();
+        self.load_firmware(core);
+
+        self.fault_dumped.store(0, Relaxed);
+        self.generation.add(1, Relaxed);
+        self.post(EventKind::Resume, 0);
+    }
+
+    /// Puts the chip into a safe state before the system goes down.
+    ///
+    /// Tells the ARC firmware that the host is going to sleep and waits
+    /// (bounded by a timeout) for the acknowledgement, then quiesces DMA if
+    /// `dev` is given. The firmware is only messaged once, whether from the
+    /// reboot notifier or the PCI shutdown callback.
+    pub(crate) fn shutdown(&self, dev: Option<&pci::Device<Core>>) {
+        if self.hw.is_identify_only() {
+            return;
+        }
+
+        if self.shut_down.xchg(1, Relaxed) == 0 {
+            if !self.hw.has_arc_msg() {
+                pr_warn!("(shutdown): cannot message {} firmware to power down\n", self.hw.name);
+            } else if let Err(err) = self.arc_msg(ArcMsg::GoToSleep) {
+                pr_warn!("(shutdown): firmware did not acknowledge power down: {err:?}\n");
+            }
+        }
+
+        if let Some(dev) = dev {
+            Self::quiesce(dev);
+        }
+    }
+
+    /// Quiesces outstanding DMA by disabling bus mastering.
+    fn quiesce(dev: &pci::Device<Core>) {
+        let pdev = crate::tt::device::pci::raw_pci_dev(dev);
+        // SAFETY: `pdev` is valid for the duration of the call.
+        unsafe { bindings::pci_clear_master(pdev) };
+    }
+
+    /// Saves the config space, to be restored after a suspend or a reset.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if `pci_save_state` fails.
+    pub(crate) fn save_state(dev: &pci::Device<Core>) -> Result {
+        let pdev = crate::tt::device::pci::raw_pci_dev(dev);
+        // SAFETY: `pdev` is valid for the duration of the call.
+        let err = unsafe { bindings::pci_save_state(pdev) };
+        ::kernel::error::to_result(err)
+    }
+
+    /// Handles a detected PCI error and returns the vote for the next recovery
+    /// step.
+    ///
+    /// Unless the channel is still functional, the chip is marked as failed
+    /// (refusing further use by open files) and its state is considered lost.
+    pub(crate) fn error_detected(&self, state: ChannelState) -> ErsResult {
+        let code = match state {
+            ChannelState::Normal => 1,
+            ChannelState::Frozen => 2,
+            ChannelState::PermFailure => 3,
+        };
+        self.post(EventKind::AerError, code);
+        match state {
+            ChannelState::Normal => {
+                self.aer.normal.add(1, Relaxed);
+                ErsResult::CanRecover
+            },
+            ChannelState::Frozen => {
+                self.aer.frozen.add(1, Relaxed);
+                self.fail();
+                ErsResult::NeedReset
+            },
+            ChannelState::PermFailure => {
+                self.aer.perm_failure.add(1, Relaxed);
+                self.fail();
+                ErsResult::Disconnect
+            },
+        }
+    }
+
+    /// Checks whether the chip responds once MMIO has been re-enabled.
+    pub(crate) fn mmio_enabled(&self, dev: &pci::Device<Core>) -> ErsResult {
+        if self.responds(dev) {
+            ErsResult::Recovered
+        } else {
+            ErsResult::NeedReset
+        }
+    }
+
+    /// Re-initializes the chip after its slot was reset.
+    pub(crate) fn slot_reset(&self, dev: &pci::Device<Core>) -> ErsResult {
+        self.aer.slot_reset.add(1, Relaxed);
+        match self.reset(dev) {
+            Ok(()) => ErsResult::Recovered,
+            Err(err) => {
+                pr_err!("(slot_reset): chip did not recover: {err:?}\n");
+                ErsResult::Disconnect
+            },
+        }
+    }
+
+    /// Marks the chip as recovered once normal operation resumes.
+    pub(crate) fn error_resume(&self) {
+        if self.failed.xchg(0, Relaxed) != 0 {
+            self.aer.recovered.add(1, Relaxed);
+        }
+    }
+
+    /// Re-initializes the chip after its PCI function was reset.
+    ///
+    /// Restores the config space and bus mastering, then checks that the chip
+    /// responds. If it does not, the function is reset again, up to
+    /// `reset_limit` times. Once it does, the power limits are applied and the
+    /// firmware images are loaded again. If it never does, a coredump is
+    /// captured.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if resetting the function fails.
+    /// * Errors with `EIO` if the chip does not respond after `reset_limit`
+    ///   resets.
+    pub(crate) fn reset(&self, dev: &pci::Device<Core>) -> Result {
+        if self.hw.is_identify_only() {
+            return Ok(());
+        }
+
+        let limit = *crate::module_parameters::reset_limit.value();
+        crate::tt::trace::reset_start(self.ordinal, limit);
+        self.post(EventKind::ResetStarted, 0);
+        let mut attempts = 0;
+        let res = self.try_reset(dev, limit, &mut attempts);
+        match res {
+            Ok(()) => {
+                self.hung.store(0, Relaxed);
+                self.fault_dumped.store(0, Relaxed);
+                self.read_firmware_info();
+                self.restore_power();
+                let core: &Device<Core> = dev.as_ref();
+                self.load_firmware(core);
+            },
+            Err(err) => self.coredump(dev, Reason::ResetFailed(err)),
+        }
+        crate::tt::trace::reset_finish(self.ordinal, attempts, &res);
+        let errno = res.err().map_or(0, |err| err.to_errno().unsigned_abs());
+        self.post(EventKind::ResetCompleted, u64::from(errno));
+        res
+    }
+
+    /// Performs the attempts of [`TtChip::reset`], counting function resets in
+    /// `attempts`.
+    fn try_reset(&self, dev: &pci::Device<Core>, limit: u32, attempts: &mut u32) -> Result {
+        let pdev = crate::tt::device::pci::raw_pci_dev(dev);
+        for attempt in 0 ..= limit {
+            if attempt > 0 {
+                pr_warn!("(reset): chip not responding; resetting ({attempt}/{limit})\n");
+                *attempts = attempt;
+                // SAFETY: `pdev` is valid and the device lock is held (by `Core`).
+                let err = unsafe { bindings::pci_reset_function_locked(pdev) };
+                ::kernel::error::to_result(err)?;
+                fsleep(Self::RESET_DELAY);
+            }
+
+            // SAFETY: `pdev` is valid for the duration of the call.
+            unsafe { bindings::pci_restore_state(pdev) };
+            // Restoring consumes the saved state, so save it again for later.
+            Self::save_state(dev)?;
+            dev.set_master();
+
+            if self.responds(dev) {
+                return Ok(());
+            }
+        }
+
+        Err(EIO)
+    }
+
+    /// Marks the chip as hung after its heartbeat did not change for
+    /// `stalled`, telling open files.
+    ///
+    /// Returns `false` if the chip was already marked as hung.
+    pub(crate) fn hang(&self, stalled: Delta) -> bool {
+        if self.hung.xchg(1, Relaxed) != 0 {
+            return false;
+        }
+        let stalled_ms = stalled.as_millis();
+        pr_err!("(watchdog): firmware heartbeat stopped for {stalled_ms} ms\n");
+        self.post(EventKind::FirmwareHung, u64::try_from(stalled_ms).unwrap_or(0));
+        true
+    }
+
+    /// Marks the chip as no longer hung after its heartbeat changed again.
+    ///
+    /// Returns `false` if the chip was not marked as hung.
+    pub(crate) fn unhang(&self) -> bool {
+        self.hung.xchg(0, Relaxed) != 0
+    }
+
+    /// Resets the hung chip of `dev` through the PCI core, which saves and
+    /// restores the config space around the reset.
+    ///
+    /// The function is reset until the chip responds, up to `reset_limit`
+    /// times (at least once). Once it does, the power limits are applied and
+    /// the firmware images are loaded again, and the chip is no longer hung.
+    /// If it never does, a coredump is captured.
+    ///
+    /// Unlike [`TtChip::reset`], this is called without the device lock, which
+    /// is only tried for each reset: it is held by unbind, suspend and
+    /// shutdown while they wait for the watchdog to stop (see
+    /// [`crate::tt::device::watchdog::TtWatchdog::stop`]).
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EAGAIN` if the device lock is held, before any reset.
+    /// * Errors if resetting the function fails.
+    /// * Errors with `EIO` if the chip does not respond after `reset_limit`
+    ///   resets.
+    pub(crate) fn recover(&self, dev: &pci::Device) -> Result {
+        let limit = (*crate::module_parameters::reset_limit.value()).max(1);
+        let pdev = crate::tt::device::pci::raw_pci_dev(dev);
+        let mut attempts = 0;
+        let mut res = Err(EIO);
+        for attempt in 1 ..= limit {
+            // SAFETY: `pdev` is valid and the device lock is not held by the
+            // caller.
+            let reset = ::kernel::error::to_result(unsafe { bindings::pci_try_reset_function(pdev) });
+            if attempt == 1 {
+                if reset == Err(EAGAIN) {
+                    return Err(EAGAIN);
+                }
+                crate::tt::trace::reset_start(self.ordinal, limit);
+                self.post(EventKind::ResetStarted, 0);
+            }
+            pr_warn!("(watchdog): reset hung chip ({attempt}/{limit})\n");
+            attempts = attempt;
+            if let Err(err) = reset {
+                res = Err(err);
+                break;
+            }
+            fsleep(Self::RESET_DELAY);
+            if self.responds(dev) {
+                res = Ok(());
+                break;
+            }
+        }
+        self.generation.add(1, Relaxed);
+        match res {
+            Ok(()) => {
+                self.hung.store(0, Relaxed);
+                self.fault_dumped.store(0, Relaxed);
+                self.read_firmware_info();
+                self.restore_power();
+                self.load_firmware(dev.as_ref());
+            },
+            Err(err) => self.coredump(dev, Reason::ResetFailed(err)),
+        }
+        crate::tt::trace::reset_finish(self.ordinal, attempts, &res);
+        let errno = res.err().map_or(0, |err| err.to_errno().unsigned_abs());
+        self.post(EventKind::ResetCompleted, u64::from(errno));
+        res
+    }
+
+    /// Returns `true` if the chip responds on the bus and (where supported)
+    /// its ARC firmware responds to messages.
+    fn responds(&self, dev: &pci::Device) -> bool {
+        let pdev = crate::tt::device::pci::raw_pci_dev(dev);
+        // SAFETY: `pdev` is valid for the duration of the call.
+        if !unsafe { bindings::pci_device_is_present(pdev) } {
+            return false;
+        }
+        match self.arc_msg(ArcMsg::AState0) {
+            Ok(_exit) => true,
+            Err(err) => err == EOPNOTSUPP,
+        }
+    }
+
+    /// Captures the state of the chip of `dev` for `reason` and submits it to
+    /// devcoredump (see [`CoreDump`]).
+    ///
+    /// Failures are logged.
+    pub(crate) fn coredump(&self, dev: &pci::Device, reason: Reason) {
+        let res = CoreDump::collect(self, dev, reason).and_then(|dump| dump.submit(dev.as_ref()));
+        match res {
+            Ok(()) => pr_info!("(coredump): captured state after {reason}\n"),
+            Err(err) => pr_warn!("(coredump): cannot capture state after {reason}: {err:?}\n"),
+        }
+    }
+
+    /// Captures a coredump after a firmware fault (see [`TtChip::coredump`]),
+    /// unless one was captured since the chip was last reset or resumed.
+    pub(crate) fn fault_coredump(&self, dev: &pci::Device) {
+        if self.fault_dumped.xchg(1, Relaxed) == 0 {
+            self.coredump(dev, Reason::FirmwareFault);
+        }
+    }
+
+    /// Marks the chip as failed, recording that its state was lost.
+    fn fail(&self) {
+        if self.failed.xchg(1, Relaxed) == 0 {
+            self.generation.add(1, Relaxed);
+        }
+    }
+}
@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: GPL-2.0
+
+/*
+ * Submission of device coredumps.
+ *
+ * `<linux/devcoredump.h>` is not part of the kernel bindings, and devcoredump
+ * takes ownership of a vmalloc() buffer, so the submission is wrapped here and
+ * called from Rust (see coredump.rs).
+ */
+
+#include <linux/devcoredump.h>
+#include <linux/errno.h>
+#include <linux/string.h>
+#include <linux/vmalloc.h>
+
+int tt_dev_coredump(struct device *dev, const void *data, size_t len);
+
+int tt_dev_coredump(struct device *dev, const void *data, size_t len)
+{
+	void *copy = vmalloc(len);
+
+	if (!copy)
+		return -ENOMEM;
+	memcpy(copy, data, len);
+	/* The copy is freed by devcoredump, also if the dump is dropped. */
+	dev_coredumpv(dev, copy, len, GFP_KERNEL);
+	return 0;
+}
@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: The dump is submitted by the C wrapper in `coredump.c`, since
+// `<linux/devcoredump.h>` is not part of the kernel bindings.
+
+use ::core::fmt::{self, Write as _};
+use ::kernel::{
+    bindings,
+    device::Device,
+    ffi::{c_int, c_void},
+    pci,
+    prelude::*,
+    str::CString,
+    sync::atomic::Relaxed,
+};
+
+use crate::tt::{
+    device::chip::{ArcLog, TtChip},
+    hw::{
+        Arch,
+        arc::{self, RESET_UNIT},
+        telemetry::Telemetry,
+    },
+};
+
+unsafe extern "C" {
+    /// Submits a copy of the `len` bytes at `data` as a coredump of `dev`.
+    fn tt_dev_coredump(dev: *mut bindings::device, data: *const c_void, len: usize) -> c_int;
+}
+
+/// The number of words of the reset unit in a dump.
+const RESET_UNIT_WORDS: usize = 0x80;
+
+/// The number of words of the PCI config space in a dump (the standard header
+/// and capabilities).
+const CONFIG_WORDS: usize = 0x40;
+
+/// The number of words per line of the hex dumps.
+const WORDS_PER_LINE: usize = 8;
+
+/// The reason for capturing a [`CoreDump`].
+#[derive(Clone, Copy)]
+pub(crate) enum Reason {
+    /// The firmware reported a fault.
+    FirmwareFault,
+    /// The heartbeat of the firmware stopped.
+    HeartbeatStopped,
+    /// The chip did not respond after `reset_limit` resets.
+    ResetFailed(Error),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::FirmwareFault => write!(f, "firmware fault"),
+            Self::HeartbeatStopped => write!(f, "stopped heartbeat"),
+            Self::ResetFailed(err) => write!(f, "failed reset ({err:?})"),
+        }
+    }
+}
+
+/// A snapshot of the state of a chip, submitted to devcoredump when its
+/// firmware faults or hangs, or it cannot be reset (see
+/// `docs/usage/coredump.md`).
+///
+/// The dump is text: the driver state, the ARC scratch registers, the reset
+/// unit registers and the PCI config space, the telemetry table, and the log of
+/// recent ARC messages. Registers which cannot be read are shown as
+/// `????????`.
+///
+/// NOTE: devcoredump keeps a single dump per device until it is read or
+/// expires, and drops further dumps in the meantime.
+pub(crate) struct CoreDump<'a> {
+    /// The chip.
+    chip: &'a TtChip,
+    /// The reason for the dump.
+    reason: Reason,
+    /// The time of the dump (`CLOCK_MONOTONIC`) in nanoseconds.
+    timestamp_ns: u64,
+    /// The version of the driver.
+    version: CString,
+    /// The ARC scratch registers (empty if their layout is not known).
+    scratch: KVec<Option<u32>>,
+    /// The reset unit registers (empty if their layout is not known).
+    reset_unit: KVec<Option<u32>>,
+    /// The PCI config space.
+    config: [Option<u32>; CONFIG_WORDS],
+    /// The telemetry table, or the error reading it.
+    telemetry: Result<Telemetry>,
+    /// The recent ARC messages.
+    arc_log: ArcLog,
+}
+
+impl<'a> CoreDump<'a> {
+    /// Captures the state of `chip` (of the PCI device `dev`) for `reason`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the dump fails.
+    pub(crate) fn collect(chip: &'a TtChip, dev: &pci::Device, reason: Reason) -> Result<Self> {
+        // SAFETY: FFI call without safety requirements.
+        let now = unsafe { bindings::ktime_get() };
+        let arc_log = chip.arc_log();
+
+        let mut scratch = KVec::new();
+        let mut reset_unit = KVec::new();
+        if matches!(chip.hw().arch, Arch::Grayskull | Arch::Wormhole) {
+            scratch.reserve(arc::SCRATCH_COUNT, GFP_KERNEL)?;
+            for index in 0 .. arc::SCRATCH_COUNT {
+                scratch.push(chip.read_reg(arc::scratch(index)).ok(), GFP_KERNEL)?;
+            }
+            reset_unit.reserve(RESET_UNIT_WORDS, GFP_KERNEL)?;
+            for word in 0 .. RESET_UNIT_WORDS {
+                reset_unit.push(chip.read_reg(RESET_UNIT + word * 4).ok(), GFP_KERNEL)?;
+            }
+        }
+
+        let pdev = crate::tt::device::pci::raw_pci_dev(dev);
+        let mut config = [None; CONFIG_WORDS];
+        for (index, value) in config.iter_mut().enumerate() {
+            let mut word = 0u32;
+            let offset = c_int::try_from(index * 4)?;
+            // SAFETY: `pdev` is valid and `word` is valid for writes.
+            let err = unsafe { bindings::pci_read_config_dword(pdev, offset, &raw mut word) };
+            *value = (err == 0).then_some(word);
+        }
+
+        Ok(Self {
+            chip,
+            reason,
+            timestamp_ns: u64::try_from(now).unwrap_or(0),
+            version: crate::tt::version::version()?,
+            scratch,
+            reset_unit,
+            config,
+            telemetry: chip.telemetry(),
+            arc_log,
+        })
+    }
+
+    /// Submits the dump to devcoredump as a dump of `dev`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENOMEM` if allocating the text of the dump fails.
+    pub(crate) fn submit(&self, dev: &Device) -> Result {
+        let mut text = Text(KVec::new());
+        write!(text, "{self}").map_err(|_err| ENOMEM)?;
+        // SAFETY: `dev` is valid and the text is valid for reads of its length
+        // for the duration of the call, which copies it.
+        let err = unsafe { tt_dev_coredump(dev.as_raw(), text.0.as_ptr().cast(), text.0.len()) };
+        ::kernel::error::to_result(err)
+    }
+}
+
+impl fmt::Display for CoreDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chip = self.chip;
+        writeln!(f, "tt_core coredump")?;
+        writeln!(f, "reason: {}", self.reason)?;
+        writeln!(f, "timestamp_ns: {}", self.timestamp_ns)?;
+        writeln!(f, "driver: {}", &*self.version)?;
+
+        writeln!(f, "\n[state]")?;
+        writeln!(f, "name: {}", chip.hw().name)?;
+        if let Some(board) = chip.board() {
+            writeln!(f, "board: {}", board.name)?;
+        }
+        writeln!(f, "ordinal: {}", chip.ordinal())?;
+        writeln!(f, "failed: {}", chip.is_failed())?;
+        writeln!(f, "hung: {}", chip.is_hung())?;
+        writeln!(f, "shut_down: {}", chip.is_shut_down())?;
+        writeln!(f, "state_generation: {}", chip.generation())?;
+        let aer = chip.aer();
+        writeln!(
+            f,
+            "aer: normal {} frozen {} perm_failure {} slot_reset {} recovered {}",
+            aer.normal.load(Relaxed),
+            aer.frozen.load(Relaxed),
+            aer.perm_failure.load(Relaxed),
+            aer.slot_reset.load(Relaxed),
+            aer.recovered.load(Relaxed)
+        )?;
+        let stats = chip.arc_stats();
+        writeln!(
+            f,
+            "arc_stats: acked {} timed_out {} failed {}",
+            stats.acked.load(Relaxed),
+            stats.timed_out.load(Relaxed),
+            stats.failed.load(Relaxed)
+        )?;
+        for (image, version) in chip.firmware_versions() {
+            let [major, minor, patch, build] = version.to_be_bytes();
+            writeln!(f, "firmware: {} {major}.{minor}.{patch}.{build}", image.name)?;
+        }
+        let mut files = 0usize;
+        chip.for_each_file(|_entry| files += 1);
+        writeln!(f, "open_files: {files}")?;
+
+        writeln!(f, "\n[arc scratch]")?;
+        if self.scratch.is_empty() {
+            writeln!(f, "not supported")?;
+        }
+        for (index, &value) in self.scratch.iter().enumerate() {
+            write!(f, "SCRATCH[{index}]: ")?;
+            write_word(f, value)?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "\n[reset unit] (BAR0 {RESET_UNIT:#010x})")?;
+        if self.reset_unit.is_empty() {
+            writeln!(f, "not supported")?;
+        }
+        write_words(f, self.reset_unit.iter().copied())?;
+
+        writeln!(f, "\n[pci config]")?;
+        write_words(f, self.config.iter().copied())?;
+
+        writeln!(f, "\n[telemetry]")?;
+        match &self.telemetry {
+            Ok(telemetry) => write_words(f, telemetry.words().iter().copied().map(Some))?,
+            Err(err) => writeln!(f, "unavailable ({err:?})")?,
+        }
+
+        writeln!(f, "\n[arc log]")?;
+        for entry in self.arc_log.iter() {
+            write!(f, "{} {:#04x} {:#010x} ", entry.timestamp_ns, entry.code, entry.arg)?;
+            match entry.reply {
+                Ok(reply) => writeln!(f, "{reply:#010x}")?,
+                Err(err) => writeln!(f, "{err:?}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes `value` as 8 hex digits, or `????????` if it is not known.
+fn write_word(f: &mut fmt::Formatter<'_>, value: Option<u32>) -> fmt::Result {
+    match value {
+        Some(value) => write!(f, "{value:08x}"),
+        None => write!(f, "????????"),
+    }
+}
+
+/// Writes `words` as lines of [`WORDS_PER_LINE`] words, each prefixed with the
+/// offset of its first word.
+fn write_words<I: IntoIterator<Item = Option<u32>>>(f: &mut fmt::Formatter<'_>, words: I) -> fmt::Result {
+    let mut count = 0;
+    for (index, value) in words.into_iter().enumerate() {
+        if index % WORDS_PER_LINE == 0 {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:04x}:", index * 4)?;
+        }
+        write!(f, " ")?;
+        write_word(f, value)?;
+        count = index + 1;
+    }
+    if count > 0 {
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+/// The text of a dump, as it is written.
+struct Text(KVec<u8>);
+
+impl fmt::Write for Text {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0
+            .extend_from_slice(s.as_bytes(), GFP_KERNEL)
+            .map_err(|_err| fmt::Error)
+    }
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::kernel::{prelude::*, str::CString, sync::Arc};
+
+use crate::tt::{
+    debugfs::DeviceDir,
+    device::{
+        chip::TtChip,
+        misc::{MiscNode, MiscOrdinal, TtMisc},
+    },
+    reboot::RebootEntry,
+};
+
+/// The software-emulated devices created by the `emulated_devices` parameter.
+///
+/// Emulated devices let the driver be tested without hardware (e.g., in a VM).
+/// Each is a Wormhole chip backed by a register model ([`crate::tt::hw::emu`])
+/// instead of a PCI device, and gets a misc device like a probed device. Since
+/// there is no PCI device, emulated devices have no interrupts, take no part in
+/// power management or error recovery, and are not registered with the
+/// compatibility overlay.
+///
+/// NOTE: The chips are created directly rather than through
+/// [`crate::tt::device::pci::TtPci`], so the PCI probe is not exercised.
+pub(crate) struct TtEmuDevices {
+    /// The emulated devices.
+    #[allow(dead_code, reason = "drop guard")]
+    devices: KVec<Pin<KBox<TtEmu>>>,
+}
+
+impl TtEmuDevices {
+    /// Creates the number of emulated devices given by the `emulated_devices`
+    /// parameter.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if creating a device fails.
+    pub(crate) fn new() -> Result<Self> {
+        let count = *crate::module_parameters::emulated_devices.value();
+        let mut devices = KVec::new();
+        if count > 0 {
+            pr_warn!("(init): creating {count} emulated devices\n");
+        }
+        for _index in 0 .. count {
+            devices.push(TtEmu::new()?, GFP_KERNEL)?;
+        }
+        Ok(Self { devices })
+    }
+}
+
+/// An emulated device.
+#[pin_data(PinnedDrop)]
+struct TtEmu {
+    /// The per-chip state.
+    chip: Arc<TtChip>,
+    /// The registration with the reboot notifier.
+    reboot: RebootEntry,
+    /// The debugfs directory.
+    debugfs: DeviceDir,
+    /// The misc device.
+    #[pin]
+    misc: MiscNode,
+    /// The ordinal for the misc device.
+    ///
+    /// NOTE: Declared after `misc` so that the name (freed along with the
+    /// ordinal) outlives the registration.
+    ordinal: MiscOrdinal,
+}
+
+#[pinned_drop]
+impl PinnedDrop for TtEmu {
+    fn drop(self: Pin<&mut Self>) {
+        // Open files may outlive the device, so tell them it is gone.
+        self.chip.remove();
+    }
+}
+
+impl TtEmu {
+    /// Creates an emulated device, registering its misc device.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if registering the misc device fails.
+    /// * Errors if allocating fails.
+    fn new() -> Result<Pin<KBox<Self>>> {
+        let hw = crate::tt::hw::HWCONFIG_WORMHOLE;
+
+        let (ordinal, init) = TtMisc::register(None)?;
+        crate::tt::trace::probe(
+            ordinal.value(),
+            u16::try_from(hw.vendor_id).unwrap_or(u16::MAX),
+            u16::try_from(hw.device_id).unwrap_or(u16::MAX),
+            hw.name,
+            ::kernel::c_str!("emulated"),
+        );
+
+        let chip = TtChip::emulated(hw, ordinal.value())?;
+        chip.read_firmware_info();
+        let reboot = RebootEntry::new(&chip)?;
+        let name = CString::try_from_fmt(fmt!("emulated.{}", ordinal.value()))?;
+        let debugfs = DeviceDir::new(&name, &chip)?;
+
+        let init = try_pin_init!(Self {
+            misc <- MiscNode::new(&chip, init),
+            chip,
+            reboot,
+            debugfs,
+            ordinal,
+        });
+        let this = KBox::pin_init(init, GFP_KERNEL)?;
+
+        this.misc.registration().device().pr_info(fmt!("registered (emulated)"));
+
+        Ok(this)
+    }
+}
@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::kernel::{
+    bindings,
+    prelude::*,
+    sync::{Arc, Mutex, poll::PollCondVar},
+    uaccess::UserSliceWriter,
+};
+
+use crate::tt::uapi::Event;
+
+/// The kind of a device state change event.
+///
+/// The discriminants are the `kind` of [`Event`] records.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum EventKind {
+    /// Events were dropped since the queue was full (`data` is the count).
+    Overflow = 1,
+    /// A reset of the chip started.
+    ResetStarted = 2,
+    /// A reset of the chip completed (`data` is the errno, or 0 on success).
+    ResetCompleted = 3,
+    /// A PCIe error was reported (`data` is the channel state).
+    AerError = 4,
+    /// The firmware reported a fault.
+    FirmwareFault = 5,
+    /// The firmware started throttling the chip (`data` is the throttler word
+    /// of its telemetry, see
+    /// [`crate::tt::hw::telemetry::TelemetryLayout::throttler`]).
+    ThermalThrottle = 6,
+    /// The chip is being suspended.
+    Suspend = 7,
+    /// The chip was resumed.
+    Resume = 8,
+    /// The device was removed.
+    Removed = 9,
+    /// The firmware heartbeat stopped (`data` is the time since it last
+    /// changed in milliseconds).
+    FirmwareHung = 10,
+}
+
+impl EventKind {
+    /// Returns an event record of this kind for a chip at `generation`.
+    fn record(self, generation: u32, data: u64) -> Event {
+        // SAFETY: FFI call without safety requirements.
+        let now = unsafe { bindings::ktime_get() };
+        #[allow(clippy::as_conversions, reason = "enum discriminant")]
+        let kind = self as u32;
+        Event {
+            kind,
+            generation,
+            timestamp_ns: u64::try_from(now).unwrap_or(0),
+            data,
+        }
+    }
+}
+
+/// The queue of events of an open file.
+///
+/// The queue holds up to [`EventQueue::CAPACITY`] events. Further events are
+/// dropped and reported by a single [`EventKind::Overflow`] event after the
+/// queued events.
+#[pin_data]
+pub(crate) struct EventQueue {
+    /// The queued events.
+    #[pin]
+    inner: Mutex<QueueInner>,
+    /// Waiters for events (readers and pollers).
+    #[pin]
+    wait: PollCondVar,
+}
+
+/// The state of an [`EventQueue`].
+struct QueueInner {
+    /// The ring of events.
+    events: [Event; EventQueue::CAPACITY],
+    /// The index of the oldest event.
+    head: usize,
+    /// The number of queued events.
+    len: usize,
+    /// The number of events dropped since the queue was full.
+    lost: u64,
+    /// Whether the device was removed.
+    removed: bool,
+}
+
+impl QueueInner {
+    /// Returns the oldest event (if any), reporting dropped events last.
+    fn front(&self) -> Option<Event> {
+        if self.len > 0 {
+            return Some(self.events[self.head]);
+        }
+        (self.lost > 0).then(|| EventKind::Overflow.record(0, self.lost))
+    }
+
+    /// Removes the event returned by [`QueueInner::front`].
+    fn pop(&mut self) {
+        if self.len > 0 {
+            self.head = (self.head + 1) % EventQueue::CAPACITY;
+            self.len -= 1;
+        } else {
+            self.lost = 0;
+        }
+    }
+}
+
+impl EventQueue {
+    /// The maximum number of queued events.
+    const CAPACITY: usize = 64;
+
+    /// Creates an empty [`EventQueue`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating fails.
+    pub(crate) fn new() -> Result<Arc<Self>> {
+        let inner = QueueInner {
+            events: [Event::default(); Self::CAPACITY],
+            head: 0,
+            len: 0,
+            lost: 0,
+            removed: false,
+        };
+        let init = pin_init!(Self {
+            inner <- ::kernel::new_mutex!(inner),
+            wait <- ::kernel::new_poll_condvar!(),
+        });
+        Arc::pin_init(init, GFP_KERNEL)
+    }
+
+    /// Queues an event of `kind` for a chip at `generation` and wakes up
+    /// waiters.
+    pub(crate) fn push(&self, kind: EventKind, generation: u32, data: u64) {
+        let event = kind.record(generation, data);
+        let mut inner = self.inner.lock();
+        if kind == EventKind::Removed {
+            inner.removed = true;
+        }
+        if inner.len == Self::CAPACITY || inner.lost > 0 {
+            inner.lost += 1;
+        } else {
+            let tail = (inner.head + inner.len) % Self::CAPACITY;
+            inner.events[tail] = event;
+            inner.len += 1;
+        }
+        drop(inner);
+        self.wait.notify_all();
+    }
+
+    /// Writes as many whole events as fit to `writer` and returns the number
+    /// of bytes written.
+    ///
+    /// Unless `nonblock`, waits for an event if the queue is empty. Returns 0
+    /// once the queue is empty after the device was removed.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EINVAL` if `writer` cannot hold an event.
+    /// * Errors with `EAGAIN` if `nonblock` and the queue is empty.
+    /// * Errors with `ERESTARTSYS` if interrupted by a signal while waiting.
+    /// * Errors if writing the first event fails.
+    pub(crate) fn read(&self, mut writer: UserSliceWriter, nonblock: bool) -> Result<usize> {
+        let size = ::core::mem::size_of::<Event>();
+        if writer.len() < size {
+            return Err(EINVAL);
+        }
+
+        let mut inner = self.inner.lock();
+        while inner.front().is_none() {
+            if inner.removed {
+                return Ok(0);
+            }
+            if nonblock {
+                return Err(EAGAIN);
+            }
+            if self.wait.wait_interruptible(&mut inner) {
+                return Err(ERESTARTSYS);
+            }
+        }
+
+        let mut written = 0;
+        while writer.len() >= size {
+            let Some(event) = inner.front() else {
+                break;
+            };
+            if let Err(err) = writer.write(&event) {
+                if written == 0 {
+                    return Err(err);
+                }
+                break;
+            }
+            inner.pop();
+            written += size;
+        }
+        Ok(written)
+    }
+
+    /// Returns the poll mask of the queue.
+    pub(crate) fn mask(&self) -> u32 {
+        let inner = self.inner.lock();
+        let mut mask = 0;
+        if inner.front().is_some() {
+            mask |= bindings::POLLIN | bindings::POLLRDNORM;
+        }
+        if inner.removed {
+            mask |= bindings::POLLHUP;
+        }
+        mask
+    }
+
+    /// Returns the waiters of the queue (to register with a poll table).
+    pub(crate) const fn wait(&self) -> &PollCondVar {
+        &self.wait
+    }
+}
@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::core::ptr::NonNull;
+use ::kernel::{
+    bindings,
+    device::Core,
+    ffi::{c_int, c_uint, c_ulong, c_void},
+    pci,
+    prelude::*,
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{Atomic, Relaxed},
+    },
+    types::ARef,
+};
+
+use crate::tt::device::{chip::TtChip, event::EventKind};
+
+/// A source of interrupts which userspace may bind an eventfd to.
+///
+/// Each source has a vector of its own (see [`IrqSource::vector`]), so the
+/// sources can only be bound once a vector is allocated for every source.
+///
+/// NOTE: The firmware interface documents neither the vectors the firmware
+/// raises the sources on nor a status register telling them apart, so the
+/// vectors of the sources are unverified. They are only allocated with the
+/// `unsafe_irq_sources` parameter set.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum IrqSource {
+    /// The ARC firmware replied to a message.
+    ArcReply,
+    /// The firmware reported a fault.
+    FirmwareFault,
+}
+
+impl IrqSource {
+    /// All sources, in order of (uAPI) number.
+    const ALL: [Self; 2] = [Self::ArcReply, Self::FirmwareFault];
+    /// The number of sources.
+    const COUNT: usize = Self::ALL.len();
+
+    /// Returns the source with the (uAPI) number `value`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EINVAL` if `value` is not a source.
+    pub(crate) fn from_u32(value: u32) -> Result<Self> {
+        let index = usize::try_from(value).map_err(|_err| EINVAL)?;
+        Self::ALL.get(index).copied().ok_or(EINVAL)
+    }
+
+    /// Returns the index of the source.
+    const fn index(self) -> usize {
+        match self {
+            Self::ArcReply => 0,
+            Self::FirmwareFault => 1,
+        }
+    }
+
+    /// Returns the MSI(-X) vector the firmware raises the interrupts of the
+    /// source on.
+    const fn vector(self) -> c_uint {
+        match self {
+            Self::ArcReply => 0,
+            Self::FirmwareFault => 1,
+        }
+    }
+}
+
+/// A reference to an eventfd context.
+struct EventFd(NonNull<bindings::eventfd_ctx>);
+
+/// SAFETY: Eventfd contexts are reference counted and may be signalled and
+/// released from any thread.
+unsafe impl Send for EventFd {}
+
+/// SAFETY: Eventfd contexts are reference counted and may be signalled and
+/// released from any thread.
+unsafe impl Sync for EventFd {}
+
+impl Drop for EventFd {
+    fn drop(&mut self) {
+        // SAFETY: The reference was taken by `eventfd_ctx_fdget`.
+        unsafe { bindings::eventfd_ctx_put(self.0.as_ptr()) };
+    }
+}
+
+impl EventFd {
+    /// Takes a reference to the eventfd context of the file descriptor `fd`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EBADF` if `fd` is not an open file descriptor.
+    /// * Errors with `EINVAL` if `fd` is not an eventfd.
+    fn get(fd: i32) -> Result<Self> {
+        // SAFETY: FFI call without safety requirements.
+        let ctx = unsafe { bindings::eventfd_ctx_fdget(fd) };
+        let ctx = ::kernel::error::from_err_ptr(ctx)?;
+        NonNull::new(ctx).map(Self).ok_or(EINVAL)
+    }
+
+    /// Adds one to the counter of the eventfd, waking up its waiters.
+    fn signal(&self) {
+        // SAFETY: The context is valid while the reference is held.
+        unsafe { bindings::eventfd_signal_mask(self.0.as_ptr(), 0) };
+    }
+}
+
+/// An eventfd bound to an interrupt source by an open file.
+struct Binding {
+    /// The identifier of the file which bound the eventfd.
+    file_id: u64,
+    /// The eventfd signalled on interrupts.
+    eventfd: EventFd,
+}
+
+/// The eventfds bound to the interrupt sources of a chip.
+#[pin_data]
+pub(crate) struct IrqEvents {
+    /// The binding of each source (by index).
+    #[pin]
+    bindings: Mutex<[Option<Binding>; IrqSource::COUNT]>,
+    /// Whether a vector is allocated for every source (and thus the sources
+    /// can be bound).
+    bindable: Atomic<u32>,
+}
+
+impl IrqEvents {
+    /// Creates [`IrqEvents`] without bindings or vectors.
+    pub(crate) fn new() -> impl PinInit<Self> {
+        pin_init!(Self {
+            bindings <- ::kernel::new_mutex!([const { None }; IrqSource::COUNT]),
+            bindable: Atomic::new(0u32),
+        })
+    }
+
+    /// Binds the eventfd `fd` to `source` for the file `file_id`, or unbinds
+    /// the eventfd of the file if `fd` is negative.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENXIO` if the vectors are not allocated.
+    /// * Errors with `EBUSY` if another file has bound an eventfd to `source`.
+    /// * Errors if `fd` is not an eventfd.
+    pub(crate) fn bind(&self, file_id: u64, source: IrqSource, fd: i32) -> Result {
+        let index = source.index();
+        let eventfd = if fd < 0 {
+            None
+        } else {
+            if self.bindable.load(Relaxed) == 0 {
+                return Err(ENXIO);
+            }
+            Some(EventFd::get(fd)?)
+        };
+        let mut bindings = self.bindings.lock();
+        let slot = &mut bindings[index];
+        if slot.as_ref().is_some_and(|binding| binding.file_id != file_id) {
+            return Err(EBUSY);
+        }
+        *slot = eventfd.map(|eventfd| Binding { file_id, eventfd });
+        Ok(())
+    }
+
+    /// Unbinds all eventfds bound by the file `file_id`.
+    pub(crate) fn unbind_file(&self, file_id: u64) {
+        for slot in self.bindings.lock().iter_mut() {
+            if slot.as_ref().is_some_and(|binding| binding.file_id == file_id) {
+                *slot = None;
+            }
+        }
+    }
+
+    /// Signals the eventfd bound to `source` (if any).
+    fn signal(&self, source: IrqSource) {
+        if let Some(binding) = &self.bindings.lock()[source.index()] {
+            binding.eventfd.signal();
+        }
+    }
+}
+
+/// A requested interrupt vector.
+struct Vector {
+    /// The Linux IRQ number of the vector.
+    irq: c_uint,
+    /// The source of the interrupts on the vector.
+    source: IrqSource,
+    /// The state of the chip.
+    chip: Arc<TtChip>,
+    /// The PCI device (for coredumps).
+    pdev: ARef<pci::Device>,
+}
+
+/// The MSI(-X) vectors of a chip and their (threaded) handlers.
+///
+/// The handlers signal the eventfds bound to the source of their vector. The
+/// handlers are freed, and the vectors released, on drop.
+pub(crate) struct TtIrqs {
+    /// The PCI device.
+    pdev: ARef<pci::Device>,
+    /// Whether vectors were allocated.
+    allocated: bool,
+    /// The requested vectors (boxed, since they are the `dev_id` of the IRQs).
+    vectors: KVec<KBox<Vector>>,
+}
+
+impl Drop for TtIrqs {
+    fn drop(&mut self) {
+        for vector in &self.vectors {
+            let dev_id = ::core::ptr::from_ref::<Vector>(vector).cast_mut().cast();
+            // SAFETY: The IRQ was requested with `dev_id` in `TtIrqs::new`.
+            unsafe { bindings::free_irq(vector.irq, dev_id) };
+        }
+        if let Some(vector) = self.vectors.first() {
+            vector.chip.events().bindable.store(0, Relaxed);
+        }
+        if self.allocated {
+            let pdev = crate::tt::device::pci::raw_pci_dev(&self.pdev);
+            // SAFETY: The vectors were allocated in `TtIrqs::new` and their IRQs
+            // have been freed.
+            unsafe { bindings::pci_free_irq_vectors(pdev) };
+        }
+    }
+}
+
+impl TtIrqs {
+    /// Allocates an MSI(-X) vector for each source of `dev` and requests
+    /// handlers for them.
+    ///
+    /// If the `unsafe_irq_sources` parameter is not set, the device supports
+    /// neither MSI-X nor MSI, or fewer vectors than sources are available, no
+    /// vectors are allocated and no sources can be bound.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if requesting a handler fails.
+    /// * Errors if allocating fails.
+    pub(crate) fn new(dev: &pci::Device<Core>, chip: &Arc<TtChip>) -> Result<Self> {
+        let pdev = crate::tt::device::pci::raw_pci_dev(dev);
+        let plain: &pci::Device = dev;
+        let mut this = Self {
+            pdev: plain.into(),
+            allocated: false,
+            vectors: KVec::new(),
+        };
+        if chip.hw().is_identify_only() || *crate::module_parameters::unsafe_irq_sources.value() == 0 {
+            return Ok(this);
+        }
+
+        #[allow(clippy::as_conversions, reason = "kernel constant")]
+        let count = IrqSource::COUNT as c_uint;
+        let flags = bindings::PCI_IRQ_MSIX | bindings::PCI_IRQ_MSI;
+        // SAFETY: `pdev` is valid and the device lock is held (by `Core`).
+        let ret = unsafe { bindings::pci_alloc_irq_vectors(pdev, count, count, flags) };
+        if ret < 0 {
+            pr_warn!("(probe): {count} MSI vectors unavailable; interrupts cannot be bound\n");
+            return Ok(this);
+        }
+        this.allocated = true;
+        this.vectors.reserve(IrqSource::COUNT, GFP_KERNEL)?;
+
+        for source in IrqSource::ALL {
+            // SAFETY: `pdev` is valid and the vector of each source is less
+            // than the allocated count.
+            let irq = unsafe { bindings::pci_irq_vector(pdev, source.vector()) };
+            let irq = c_uint::try_from(irq).map_err(|_err| Error::from_errno(irq))?;
+            let vector = KBox::new(
+                Vector {
+                    irq,
+                    source,
+                    chip: Arc::clone(chip),
+                    pdev: plain.into(),
+                },
+                GFP_KERNEL,
+            )?;
+            let dev_id = ::core::ptr::from_ref::<Vector>(&vector).cast_mut().cast::<c_void>();
+            // SAFETY: Arguments are valid for API. `dev_id` is boxed and lives in
+            // `vectors` until the IRQ is freed on drop.
+            let ret = unsafe {
+                bindings::request_threaded_irq(
+                    irq,
+                    None,
+                    Some(Self::thread_fn),
+                    c_ulong::from(bindings::IRQF_ONESHOT),
+                    ::kernel::c_str!("tt_core").as_char_ptr(),
+                    dev_id,
+                )
+            };
+            ::kernel::error::to_result(ret)?;
+            if let Err(err) = this.vectors.push(vector, GFP_KERNEL) {
+                // SAFETY: The IRQ was just requested with `dev_id`.
+                unsafe { bindings::free_irq(irq, dev_id) };
+                return Err(err.into());
+            }
+        }
+
+        chip.events().bindable.store(1, Relaxed);
+        Ok(this)
+    }
+
+    /// Handles an interrupt in process context, signalling the bound eventfd
+    /// (and queueing an event and capturing a coredump for firmware faults, see
+    /// [`TtChip::fault_coredump`]).
+    extern "C" fn thread_fn(_irq: c_int, dev_id: *mut c_void) -> bindings::irqreturn_t {
+        // SAFETY: `dev_id` is the `Vector` the IRQ was requested with, which
+        // outlives the registration.
+        let vector = unsafe { &*dev_id.cast::<Vector>() };
+        if vector.source == IrqSource::FirmwareFault {
+            vector.chip.fault_coredump(&vector.pdev);
+            vector.chip.post(EventKind::FirmwareFault, 0);
+        }
+        vector.chip.events().signal(vector.source);
+        bindings::irqreturn_IRQ_HANDLED
+    }
+}
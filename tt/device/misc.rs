@@ -1,12 +1,39 @@
 // SPDX-License-Identifier: GPL-2.0
 
+use ::core::{mem::MaybeUninit, ptr::NonNull};
 use ::kernel::{
-    miscdevice::{MiscDeviceOptions, MiscDeviceRegistration},
+    bindings::{self, attribute, attribute_group, device, device_attribute},
+    fs::{File, file::flags},
+    ioctl::_IOC_SIZE,
+    miscdevice::{AttributeGroups, MiscDeviceOptions, MiscDeviceRegistration},
+    mm::virt::VmaNew,
     prelude::*,
-    types::ARef,
+    str::{BStr, parse_int::ParseInt as _},
+    sync::{
+        Arc,
+        atomic::{Atomic, Relaxed},
+        poll::PollTable,
+    },
+    types::{ARef, Opaque},
+    uaccess::{UserPtr, UserSlice, UserSliceReader, UserSliceWriter},
 };
 
 pub(crate) use self::misc::MiscOrdinal;
+use crate::tt::{
+    device::{chip::TtChip, event::EventQueue, irq::IrqSource},
+    hw::flash::{self, BUNDLE_HEADER_SIZE},
+    uapi::{
+        self,
+        BindEventfd,
+        FlashBundle,
+        FlashIo,
+        POWER_STATE_BUSY,
+        POWER_STATE_IDLE,
+        PowerLimits,
+        PowerState,
+        Topology,
+    },
+};
 
 /// A misc device reference.
 #[allow(clippy::empty_structs_with_brackets, reason = "rust-for-linux")]
@@ -15,75 +42,1133 @@ pub(crate) struct TtMisc {
     #[pin]
     inner: ::kernel::sync::Mutex<i32>,
     dev: ARef<::kernel::device::Device>,
+    /// The access mode the file was opened with.
+    mode: OpenMode,
+    /// The state of the chip the file was opened for.
+    chip: Arc<TtChip>,
+    /// The identifier of the file with the chip.
+    file_id: u64,
+    /// The queue of device state change events.
+    queue: Arc<EventQueue>,
+    /// Whether the file hinted that it is busy (see
+    /// [`uapi::IOCTL_SET_POWER_STATE`]), changed under the lock of the count
+    /// of busy files of the chip (see [`TtChip::hint_busy`]).
+    busy: Atomic<u32>,
+}
+
+/// A registered misc device and the chip it gives access to.
+///
+/// Embedded in each device (PCI or emulated), so that files opened through the
+/// registration can find the chip.
+#[pin_data]
+pub(crate) struct MiscNode {
+    /// The state of the chip.
+    chip: Arc<TtChip>,
+    /// The registration for the misc device.
+    #[pin]
+    reg: MiscDeviceRegistration<TtMisc>,
+}
+
+impl MiscNode {
+    /// Creates a [`MiscNode`] for `chip` from the registration `reg` (see
+    /// [`TtMisc::register`]).
+    pub(crate) fn new<R: PinInit<MiscDeviceRegistration<TtMisc>, Error>>(
+        chip: &Arc<TtChip>,
+        reg: R,
+    ) -> impl PinInit<Self, Error> {
+        try_pin_init!(Self {
+            chip: Arc::clone(chip),
+            reg <- reg,
+        })
+    }
+
+    /// Returns the registration for the misc device.
+    pub(crate) const fn registration(&self) -> &MiscDeviceRegistration<TtMisc> {
+        &self.reg
+    }
+
+    /// Returns the [`MiscNode`] which contains the registration `reg`.
+    ///
+    /// # Safety
+    ///
+    /// `reg` must be the `reg` field of a [`MiscNode`].
+    unsafe fn from_registration(reg: &MiscDeviceRegistration<TtMisc>) -> &Self {
+        let reg = ::core::ptr::from_ref(reg);
+        // SAFETY: By the safety requirements, `reg` is embedded in a `MiscNode`.
+        let this = unsafe { ::kernel::container_of!(reg, Self, reg) };
+        // SAFETY: The containing `MiscNode` outlives its field.
+        unsafe { &*this }
+    }
+
+    /// Returns the [`MiscNode`] of the misc device `dev`.
+    ///
+    /// The driver data of a misc device is its `struct miscdevice` (set by
+    /// `misc_register`), which is the registration embedded in the node.
+    ///
+    /// # Safety
+    ///
+    /// `dev` must be a registered misc device created by [`TtMisc::register`], and
+    /// the returned reference must not outlive an attribute callback.
+    unsafe fn of_device<'dev>(dev: *mut device) -> Option<&'dev Self> {
+        // SAFETY: By the safety requirements, `dev` is valid.
+        let data = unsafe { bindings::dev_get_drvdata(dev) };
+        let reg = NonNull::new(data.cast::<MiscDeviceRegistration<TtMisc>>())?;
+        // SAFETY: `MiscDeviceRegistration` is a transparent wrapper of the
+        // `struct miscdevice`, which is valid while `dev` is registered.
+        let reg = unsafe { reg.as_ref() };
+        // SAFETY: Every `TtMisc` registration is the `reg` of a `MiscNode`.
+        Some(unsafe { Self::from_registration(reg) })
+    }
+}
+
+/// The access mode of an open misc device file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum OpenMode {
+    /// Opened read-only: may query info and telemetry but may not map memory or
+    /// perform DMA.
+    Monitor,
+    /// Opened for writing: full access.
+    Control,
+}
+
+impl OpenMode {
+    /// Returns the access mode for `file`.
+    fn of(file: &File) -> Self {
+        if file.flags() & flags::O_ACCMODE == flags::O_RDONLY {
+            Self::Monitor
+        } else {
+            Self::Control
+        }
+    }
 }
 
 #[vtable]
 impl ::kernel::miscdevice::MiscDevice for TtMisc {
     type Ptr = Pin<KBox<Self>>;
 
-    fn open(
-        _file: &::kernel::fs::File,
-        misc: &::kernel::miscdevice::MiscDeviceRegistration<Self>,
-    ) -> Result<Self::Ptr> {
+    fn open(file: &File, misc: &::kernel::miscdevice::MiscDeviceRegistration<Self>) -> Result<Self::Ptr> {
         let dev = ARef::from(misc.device());
-        dev_info!(dev, "opening\n");
+        // SAFETY: Every `TtMisc` registration is the `reg` of a `MiscNode`.
+        let chip = Arc::clone(&unsafe { MiscNode::from_registration(misc) }.chip);
+        let mode = OpenMode::of(file);
+        let queue = EventQueue::new()?;
+        let file_id = chip.add_file(mode, &queue)?;
+        if mode == OpenMode::Monitor {
+            dev_info!(dev, "opening (monitor)\n");
+        } else {
+            dev_info!(dev, "opening\n");
+        }
         KBox::try_pin_init(
             try_pin_init! {
                 TtMisc {
                     inner <- ::kernel::new_mutex!(0i32),
                     dev,
+                    mode,
+                    chip: Arc::clone(&chip),
+                    file_id,
+                    queue,
+                    busy: Atomic::new(0u32),
                 }
             },
             GFP_KERNEL,
         )
+        .inspect_err(|_err| chip.remove_file(file_id))
+    }
+
+    fn mmap(this: Pin<&Self>, _file: &File, _vma: &VmaNew) -> Result {
+        this.require_control()?;
+        // NOTE: There are no mappable regions yet.
+        Err(EINVAL)
     }
+
+    fn read(this: Pin<&Self>, file: &File, writer: UserSliceWriter, _offset: &mut i64) -> Result<usize> {
+        let nonblock = file.flags() & flags::O_NONBLOCK != 0;
+        this.queue.read(writer, nonblock)
+    }
+
+    fn poll(this: Pin<&Self>, file: &File, table: &mut PollTable) -> u32 {
+        table.register_wait(file, this.queue.wait());
+        this.queue.mask()
+    }
+
+    fn ioctl(this: Pin<&Self>, _file: &File, cmd: u32, arg: usize) -> Result<isize> {
+        let ordinal = this.chip.ordinal();
+        crate::tt::trace::ioctl_enter(ordinal, cmd);
+        // SAFETY: FFI call without safety requirements.
+        let start = unsafe { bindings::ktime_get() };
+        let res = this.dispatch(cmd, arg);
+        // SAFETY: FFI call without safety requirements.
+        let latency = unsafe { bindings::ktime_get() } - start;
+        crate::tt::trace::ioctl_exit(ordinal, cmd, &res, latency);
+        res
+    }
+}
+
+/// Returns `true` if the current task has `CAP_SYS_ADMIN`.
+fn capable_sys_admin() -> bool {
+    #[allow(clippy::as_conversions, reason = "kernel constant")]
+    let cap = bindings::CAP_SYS_ADMIN as i32;
+    // SAFETY: FFI call without safety requirements.
+    unsafe { bindings::capable(cap) }
+}
+
+/// Returns `true` if erasing and writing the SPI flash is refused (the
+/// `flash_write_protect` parameter is set).
+fn flash_write_protected() -> bool {
+    *crate::module_parameters::flash_write_protect.value() != 0
 }
 
 impl TtMisc {
     /// The devfs prefix for the misc device.
     const DEVICE_PREFIX: &CStr = ::kernel::c_str!("tenstorrent");
 
-    /// Registers a misc device and returns the ordinal and registration.
+    /// Registers a misc device (as a child of `parent`, if any) and returns the
+    /// ordinal and registration.
+    ///
+    /// The sysfs attributes (see [`GROUPS`]) are created along with the misc
+    /// device, before it is announced to userspace.
+    ///
+    /// The registration must be embedded in a [`MiscNode`].
+    ///
+    /// The device node is created with the mode and group of the `dev_mode` and
+    /// `dev_gid` parameters, if set.
     ///
     /// # Errors
     ///
+    /// * Errors with `EINVAL` if `dev_mode` has bits other than the permission
+    ///   bits.
     /// * Errors if obtaining the next ordinal/name pair fails.
-    pub(crate) fn register() -> Result<(MiscOrdinal, impl PinInit<MiscDeviceRegistration<Self>, Error>)> {
+    pub(crate) fn register(
+        parent: Option<&::kernel::device::Device>,
+    ) -> Result<(MiscOrdinal, impl PinInit<MiscDeviceRegistration<Self>, Error>)> {
+        let mode = *crate::module_parameters::dev_mode.value();
+        if mode & !0o777 != 0 {
+            pr_err!("Cannot create misc device: `dev_mode` {mode:#o} is not a permission mode\n");
+            return Err(EINVAL);
+        }
         let (ordinal, name) = self::supply::MISC_SUPPLY.next().inspect_err(|err| {
             if err == &ENOSPC {
                 pr_err!("Cannot create misc device: `max_devices` already allocated\n");
             }
         })?;
-        let options = MiscDeviceOptions { name };
+        let gid = u32::try_from(*crate::module_parameters::dev_gid.value()).unwrap_or(0);
+        let groups = Some(&GROUPS);
+        let options = MiscDeviceOptions {
+            name,
+            parent,
+            mode,
+            gid,
+            groups,
+        };
         let reg = MiscDeviceRegistration::register(options);
         Ok((ordinal, reg))
     }
+
+    /// Notifies pollers of the `state_generation` attribute that the device
+    /// state was lost.
+    pub(crate) fn notify_state_lost(reg: &MiscDeviceRegistration<Self>) {
+        let dev = reg.device().as_raw();
+        let name = ::kernel::c_str!("state_generation").as_char_ptr();
+        // SAFETY: `dev` is a registered device.
+        let kobj = unsafe { &raw mut (*dev).kobj };
+        // SAFETY: `kobj` is valid and `name` is a static string.
+        unsafe { bindings::sysfs_notify(kobj, ::core::ptr::null(), name) };
+    }
+
+    /// Calls `f` with each assigned misc device ordinal and its devfs name.
+    pub(crate) fn for_each_ordinal<F: FnMut(usize, &CStr)>(f: F) {
+        self::supply::MISC_SUPPLY.for_each(f);
+    }
+
+    /// Checks that the file was not opened in [`OpenMode::Monitor`].
+    ///
+    /// Operations which map device memory or perform DMA must call this first.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EPERM` if the file was opened in [`OpenMode::Monitor`].
+    pub(crate) fn require_control(&self) -> Result {
+        if self.mode == OpenMode::Monitor {
+            return Err(EPERM);
+        }
+        Ok(())
+    }
+
+    /// Handles the ioctl `cmd` with the (user pointer) argument `arg`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ENOTTY` if `cmd` is not an ioctl of the device.
+    /// * Errors if handling the ioctl fails.
+    fn dispatch(&self, cmd: u32, arg: usize) -> Result<isize> {
+        let user = UserSlice::new(UserPtr::from_addr(arg), _IOC_SIZE(cmd));
+        match cmd {
+            uapi::IOCTL_BIND_EVENTFD => self.bind_eventfd(user.reader()),
+            uapi::IOCTL_GET_TOPOLOGY => self.get_topology(user.writer()),
+            uapi::IOCTL_GET_HARVESTING => self.get_harvesting(user.writer()),
+            uapi::IOCTL_FLASH_INFO => self.flash_info(user.writer()),
+            uapi::IOCTL_FLASH_READ => self.flash_read(user.reader()),
+            uapi::IOCTL_FLASH_ERASE => self.flash_erase(user.reader()),
+            uapi::IOCTL_FLASH_WRITE => self.flash_write(user.reader()),
+            uapi::IOCTL_GET_POWER_LIMITS => self.get_power_limits(user.writer()),
+            uapi::IOCTL_SET_POWER_LIMITS => self.set_power_limits(user.reader()),
+            uapi::IOCTL_SET_POWER_STATE => self.set_power_state(user.reader()),
+            _ => Err(ENOTTY),
+        }
+    }
+
+    /// Handles [`uapi::IOCTL_BIND_EVENTFD`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors if reading the argument fails.
+    /// * Errors if binding the eventfd fails.
+    fn bind_eventfd(&self, mut reader: UserSliceReader) -> Result<isize> {
+        let args = reader.read::<BindEventfd>()?;
+        let source = IrqSource::from_u32(args.source)?;
+        self.chip.events().bind(self.file_id, source, args.fd)?;
+        Ok(0)
+    }
+
+    /// Handles [`uapi::IOCTL_GET_TOPOLOGY`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EIO` if the chip has failed.
+    /// * Errors if reading the topology fails.
+    /// * Errors if writing the result fails.
+    fn get_topology(&self, mut writer: UserSliceWriter) -> Result<isize> {
+        self.chip.check_alive()?;
+        let topology = self.chip.topology()?;
+        writer.write(&topology)?;
+        Ok(0)
+    }
+
+    /// Handles [`uapi::IOCTL_GET_HARVESTING`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EIO` if the chip has failed.
+    /// * Errors if reading the harvesting fails.
+    /// * Errors if writing the result fails.
+    fn get_harvesting(&self, mut writer: UserSliceWriter) -> Result<isize> {
+        self.chip.check_alive()?;
+        let harvesting = self.chip.harvesting()?;
+        writer.write(&harvesting)?;
+        Ok(0)
+    }
+
+    /// Checks that the file may erase and write the SPI flash.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EROFS` if the `flash_write_protect` parameter is set.
+    /// * Errors with `EPERM` if the file was opened in [`OpenMode::Monitor`].
+    /// * Errors with `EPERM` if the task does not have `CAP_SYS_ADMIN`.
+    fn require_flash_write(&self) -> Result {
+        if flash_write_protected() {
+            return Err(EROFS);
+        }
+        self.require_control()?;
+        if !capable_sys_admin() {
+            return Err(EPERM);
+        }
+        Ok(())
+    }
+
+    /// Handles [`uapi::IOCTL_FLASH_INFO`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the flash layout is not known.
+    /// * Errors if writing the result fails.
+    fn flash_info(&self, mut writer: UserSliceWriter) -> Result<isize> {
+        let layout = self.chip.flash_layout()?;
+        writer.write(&layout.info(flash_write_protected()))?;
+        Ok(0)
+    }
+
+    /// Handles [`uapi::IOCTL_FLASH_READ`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EIO` if the chip has failed.
+    /// * Errors with `EINVAL` if the range is misaligned or out of bounds.
+    /// * Errors if reading the flash fails.
+    /// * Errors if reading the argument or writing the result fails.
+    fn flash_read(&self, mut reader: UserSliceReader) -> Result<isize> {
+        let args = reader.read::<FlashIo>()?;
+        self.chip.check_alive()?;
+        let layout = self.chip.flash_layout()?;
+        layout.check_range(args.offset, args.length)?;
+
+        let data = UserPtr::from_addr(usize::try_from(args.data)?);
+        let mut writer = UserSlice::new(data, usize::try_from(args.length)?).writer();
+        let mut block = KVec::from_elem(0u8, usize::try_from(layout.block_size)?, GFP_KERNEL)?;
+        for offset in (args.offset .. args.offset + args.length).step_by(block.len()) {
+            self.chip.flash_read_block(offset, &mut block)?;
+            writer.write_slice(&block)?;
+        }
+        Ok(0)
+    }
+
+    /// Handles [`uapi::IOCTL_FLASH_ERASE`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors if the file may not write the flash (see
+    ///   [`Self::require_flash_write`]).
+    /// * Errors with `EIO` if the chip has failed.
+    /// * Errors if the range is not writable (see
+    ///   [`FlashLayout::check_writable`](crate::tt::hw::flash::FlashLayout::check_writable)).
+    /// * Errors if erasing the flash fails.
+    /// * Errors if reading the argument fails.
+    fn flash_erase(&self, mut reader: UserSliceReader) -> Result<isize> {
+        self.require_flash_write()?;
+        let args = reader.read::<FlashIo>()?;
+        self.chip.check_alive()?;
+        let layout = self.chip.flash_layout()?;
+        layout.check_writable(args.offset, args.length)?;
+
+        let block = KVec::from_elem(0xFFu8, usize::try_from(layout.block_size)?, GFP_KERNEL)?;
+        for offset in (args.offset .. args.offset + args.length).step_by(block.len()) {
+            self.chip.flash_write_block(offset, &block)?;
+        }
+        Ok(0)
+    }
+
+    /// Handles [`uapi::IOCTL_FLASH_WRITE`].
+    ///
+    /// The whole bundle is copied in and its signature verified before the
+    /// first block is written.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if the file may not write the flash (see
+    ///   [`Self::require_flash_write`]).
+    /// * Errors with `EINVAL` if the offset is not zero.
+    /// * Errors with `EIO` if the chip has failed.
+    /// * Errors if the bundle is malformed (see
+    ///   [`FlashLayout::check_bundle`](crate::tt::hw::flash::FlashLayout::check_bundle)).
+    /// * Errors if verifying the signature fails (see
+    ///   [`crate::tt::verify::signature`]).
+    /// * Errors if writing the flash fails.
+    /// * Errors if reading the argument or the bundle fails.
+    fn flash_write(&self, mut reader: UserSliceReader) -> Result<isize> {
+        self.require_flash_write()?;
+        let args = reader.read::<FlashIo>()?;
+        if args.offset != 0 {
+            return Err(EINVAL);
+        }
+        self.chip.check_alive()?;
+        let layout = self.chip.flash_layout()?;
+
+        let length = usize::try_from(args.length)?;
+        let data = UserPtr::from_addr(usize::try_from(args.data)?);
+        let mut bundle = UserSlice::new(data, length).reader();
+        let header = bundle.read::<FlashBundle>()?;
+        layout.check_bundle(self.chip.hw().device_id, &header, length)?;
+        let mut signature = KVec::from_elem(0u8, usize::try_from(header.signature_length)?, GFP_KERNEL)?;
+        bundle.read_slice(&mut signature)?;
+        // The header is signed along with the image, so that a signed image
+        // cannot be written at another offset or to another chip.
+        let mut signed = KVVec::from_elem(0u8, BUNDLE_HEADER_SIZE + usize::try_from(header.length)?, GFP_KERNEL)?;
+        signed[.. BUNDLE_HEADER_SIZE].copy_from_slice(&flash::signed_header(&header));
+        bundle.read_slice(&mut signed[BUNDLE_HEADER_SIZE ..])?;
+        crate::tt::verify::signature(&signed, &signature)?;
+
+        let block_size = usize::try_from(layout.block_size)?;
+        for (offset, block) in (header.offset ..)
+            .step_by(block_size)
+            .zip(signed[BUNDLE_HEADER_SIZE ..].chunks_exact(block_size))
+        {
+            self.chip.flash_write_block(offset, block)?;
+        }
+        pr_info!(
+            "(flash): wrote {:#x} bytes at {:#x} on device {}\n",
+            header.length,
+            header.offset,
+            self.chip.ordinal()
+        );
+        Ok(0)
+    }
+
+    /// Handles [`uapi::IOCTL_GET_POWER_LIMITS`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the bounds of the board are not known.
+    /// * Errors if writing the result fails.
+    fn get_power_limits(&self, mut writer: UserSliceWriter) -> Result<isize> {
+        let info = self.chip.power_info()?;
+        writer.write(&info)?;
+        Ok(0)
+    }
+
+    /// Handles [`uapi::IOCTL_SET_POWER_LIMITS`].
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EPERM` if the file was opened in [`OpenMode::Monitor`].
+    /// * Errors with `EPERM` if the task does not have `CAP_SYS_ADMIN`.
+    /// * Errors with `EIO` if the chip has failed.
+    /// * Errors if setting the limits fails (see
+    ///   [`TtChip::update_power_limits`]).
+    /// * Errors if reading the argument fails.
+    fn set_power_limits(&self, mut reader: UserSliceReader) -> Result<isize> {
+        self.require_control()?;
+        if !capable_sys_admin() {
+            return Err(EPERM);
+        }
+        let limits = reader.read::<PowerLimits>()?;
+        self.chip.check_alive()?;
+        self.chip.update_power_limits(|current| *current = limits)?;
+        Ok(0)
+    }
+
+    /// Handles [`uapi::IOCTL_SET_POWER_STATE`].
+    ///
+    /// Hinting the state the file is already in does nothing.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EPERM` if the file was opened in [`OpenMode::Monitor`].
+    /// * Errors with `EINVAL` if the state is unknown or `reserved` is not
+    ///   zero.
+    /// * Errors with `EOPNOTSUPP` if the firmware cannot be messaged.
+    /// * Errors with `EIO` if the chip has failed.
+    /// * Errors if changing the power state fails (see [`TtChip::hint_busy`]).
+    /// * Errors if reading the argument fails.
+    fn set_power_state(&self, mut reader: UserSliceReader) -> Result<isize> {
+        self.require_control()?;
+        let args = reader.read::<PowerState>()?;
+        let busy = match args.state {
+            POWER_STATE_IDLE => false,
+            POWER_STATE_BUSY => true,
+            _ => return Err(EINVAL),
+        };
+        if args.reserved != 0 {
+            return Err(EINVAL);
+        }
+        if self.chip.hw().power_states.is_none() {
+            return Err(EOPNOTSUPP);
+        }
+        self.chip.check_alive()?;
+        self.chip.hint_busy(&self.busy, busy)?;
+        Ok(0)
+    }
 }
 
+/// The `pci_bdf` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows the PCI bus/device/function (e.g., `0000:05:00.0`) of the parent. Fails
+/// with `ENODEV` for emulated devices, which have no parent.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_PCI_BDF: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let parent = unsafe { (*dev).parent };
+        let Some(parent) = NonNull::new(parent) else {
+            #[allow(clippy::as_conversions, reason = "kernel error value")]
+            let res = ENODEV.to_errno() as isize;
+            return res;
+        };
+        // SAFETY: The parent is referenced by `dev` while it is registered.
+        let name = unsafe { (*parent.as_ptr()).kobj.name };
+        let fmt = ::kernel::c_str!("%s\n").as_char_ptr();
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        let res = unsafe { bindings::sysfs_emit(buf, fmt, name) };
+        #[allow(clippy::as_conversions, reason = "kernel returned value")]
+        let res = res as isize;
+        res
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"pci_bdf".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `state_generation` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows the number of times the device state was lost (e.g., across a
+/// suspend or a PCI error). The attribute can be polled for changes.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_STATE_GENERATION: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let Some(node) = (unsafe { MiscNode::of_device(dev) }) else {
+            #[allow(clippy::as_conversions, reason = "kernel error value")]
+            let res = ENODEV.to_errno() as isize;
+            return res;
+        };
+        let generation = node.chip.generation();
+        let fmt = ::kernel::c_str!("%u\n").as_char_ptr();
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        let res = unsafe { bindings::sysfs_emit(buf, fmt, generation) };
+        #[allow(clippy::as_conversions, reason = "kernel returned value")]
+        let res = res as isize;
+        res
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"state_generation".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `aer_counts` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows the counts of PCI error recovery events, one `name count` pair per
+/// line (as in the `aer_dev_*` attributes of the PCI device).
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_AER_COUNTS: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let Some(node) = (unsafe { MiscNode::of_device(dev) }) else {
+            #[allow(clippy::as_conversions, reason = "kernel error value")]
+            let res = ENODEV.to_errno() as isize;
+            return res;
+        };
+        let aer = node.chip.aer();
+        let fmt = ::kernel::c_str!("normal %u\nfrozen %u\nperm_failure %u\nslot_reset %u\nrecovered %u\n");
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        let res = unsafe {
+            bindings::sysfs_emit(
+                buf,
+                fmt.as_char_ptr(),
+                aer.normal.load(Relaxed),
+                aer.frozen.load(Relaxed),
+                aer.perm_failure.load(Relaxed),
+                aer.slot_reset.load(Relaxed),
+                aer.recovered.load(Relaxed),
+            )
+        };
+        #[allow(clippy::as_conversions, reason = "kernel returned value")]
+        let res = res as isize;
+        res
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"aer_counts".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// Returns the Ethernet topology of the chip of the misc device `dev`, or the
+/// (negative) errno to return from an attribute callback.
+///
+/// # Safety
+///
+/// `dev` must be a registered misc device created by [`TtMisc::register`].
+unsafe fn attr_topology(dev: *mut device) -> Result<Topology, isize> {
+    // SAFETY: By the safety requirements, `dev` is a registered misc device.
+    let node = unsafe { MiscNode::of_device(dev) }.ok_or(ENODEV);
+    let res = node.and_then(|node| {
+        node.chip.check_alive()?;
+        node.chip.topology()
+    });
+    res.map_err(|err| {
+        #[allow(clippy::as_conversions, reason = "kernel error value")]
+        let errno = err.to_errno() as isize;
+        errno
+    })
+}
+
+/// The `eth_coord` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows the coordinates of the chip in its Ethernet-connected system as
+/// `rack shelf x y`.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_ETH_COORD: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let topology = match unsafe { attr_topology(dev) } {
+            Ok(topology) => topology,
+            Err(err) => return err,
+        };
+        let local = topology.local;
+        let fmt = ::kernel::c_str!("%u %u %u %u\n").as_char_ptr();
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        let res = unsafe {
+            bindings::sysfs_emit(
+                buf,
+                fmt,
+                u32::from(local.rack),
+                u32::from(local.shelf),
+                u32::from(local.x),
+                u32::from(local.y),
+            )
+        };
+        #[allow(clippy::as_conversions, reason = "kernel returned value")]
+        let res = res as isize;
+        res
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"eth_coord".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `eth_links` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows the Ethernet links of the chip, one line per port:
+///
+/// ```text
+/// <port> <state> <remote board ID> <remote rack> <remote shelf> <remote x> <remote y> <remote port>
+/// ```
+///
+/// The state is `unknown`, `down` or `up`. The remote fields are zero unless the
+/// link is up.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_ETH_LINKS: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let topology = match unsafe { attr_topology(dev) } {
+            Ok(topology) => topology,
+            Err(err) => return err,
+        };
+        let count = usize::try_from(topology.link_count).unwrap_or(0);
+        let fmt = ::kernel::c_str!("%u %s %016llx %u %u %u %u %u\n").as_char_ptr();
+        let mut len = 0;
+        for (port, link) in (0u32 ..).zip(topology.links.iter().take(count)) {
+            let state = match link.state {
+                1 => ::kernel::c_str!("down"),
+                2 => ::kernel::c_str!("up"),
+                _ => ::kernel::c_str!("unknown"),
+            };
+            let remote = link.remote;
+            // SAFETY: Arguments are valid for API. Error is propagated on failure.
+            let res = unsafe {
+                bindings::sysfs_emit_at(
+                    buf,
+                    len,
+                    fmt,
+                    port,
+                    state.as_char_ptr(),
+                    link.remote_board_id,
+                    u32::from(remote.rack),
+                    u32::from(remote.shelf),
+                    u32::from(remote.x),
+                    u32::from(remote.y),
+                    link.remote_port,
+                )
+            };
+            len += res;
+        }
+        #[allow(clippy::as_conversions, reason = "kernel returned value")]
+        let len = len as isize;
+        len
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"eth_links".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `harvesting` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows the units of the chip disabled by harvesting, one `name mask` pair per
+/// line (see [`uapi::Harvesting`]).
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_HARVESTING: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let node = unsafe { MiscNode::of_device(dev) }.ok_or(ENODEV);
+        let res = node.and_then(|node| {
+            node.chip.check_alive()?;
+            node.chip.harvesting()
+        });
+        let harvesting = match res {
+            Ok(harvesting) => harvesting,
+            Err(err) => {
+                #[allow(clippy::as_conversions, reason = "kernel error value")]
+                let errno = err.to_errno() as isize;
+                return errno;
+            },
+        };
+        let fmt = ::kernel::c_str!("tensix_rows 0x%x\ntensix_cols 0x%x\ndram_banks 0x%x\neth_cores 0x%x\n");
+        // SAFETY: Arguments are valid for API. Error is propagated on failure.
+        let res = unsafe {
+            bindings::sysfs_emit(
+                buf,
+                fmt.as_char_ptr(),
+                harvesting.tensix_rows,
+                harvesting.tensix_cols,
+                harvesting.dram_banks,
+                harvesting.eth_cores,
+            )
+        };
+        #[allow(clippy::as_conversions, reason = "kernel returned value")]
+        let res = res as isize;
+        res
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"harvesting".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// The `firmware_versions` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows the firmware images loaded by the driver, one `name version` pair per
+/// line (e.g., `erisc 6.10.0.0`), with `none` for images which are not loaded.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_FIRMWARE_VERSIONS: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` is valid for the duration of the callback.
+        let Some(node) = (unsafe { MiscNode::of_device(dev) }) else {
+            #[allow(clippy::as_conversions, reason = "kernel error value")]
+            let errno = ENODEV.to_errno() as isize;
+            return errno;
+        };
+        let mut len = 0;
+        for (image, version) in node.chip.firmware_versions() {
+            let name = image.name.as_char_ptr();
+            let res = if version == 0 {
+                let fmt = ::kernel::c_str!("%s none\n").as_char_ptr();
+                // SAFETY: Arguments are valid for API. Error is propagated on failure.
+                unsafe { bindings::sysfs_emit_at(buf, len, fmt, name) }
+            } else {
+                let [major, minor, patch, build] = version.to_be_bytes().map(u32::from);
+                let fmt = ::kernel::c_str!("%s %u.%u.%u.%u\n").as_char_ptr();
+                // SAFETY: Arguments are valid for API. Error is propagated on failure.
+                unsafe { bindings::sysfs_emit_at(buf, len, fmt, name, major, minor, patch, build) }
+            };
+            len += res;
+        }
+        #[allow(clippy::as_conversions, reason = "kernel returned value")]
+        let len = len as isize;
+        len
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"firmware_versions".as_ptr().cast(),
+            mode: 0o444,
+        },
+        show: Some(show),
+        store: None,
+    })
+};
+
+/// Shows the power limit `field` of the chip of the misc device `dev`.
+///
+/// # Safety
+///
+/// `dev` must be a registered misc device created by [`TtMisc::register`], and
+/// `buf` the page buffer of an attribute callback.
+unsafe fn show_power_limit(dev: *mut device, buf: *mut ::kernel::ffi::c_char, field: fn(&PowerLimits) -> u32) -> isize {
+    // SAFETY: By the safety requirements, `dev` is a registered misc device.
+    let node = unsafe { MiscNode::of_device(dev) }.ok_or(ENODEV);
+    let info = match node.and_then(|node| node.chip.power_info()) {
+        Ok(info) => info,
+        Err(err) => {
+            #[allow(clippy::as_conversions, reason = "kernel error value")]
+            let errno = err.to_errno() as isize;
+            return errno;
+        },
+    };
+    let fmt = ::kernel::c_str!("%u\n").as_char_ptr();
+    // SAFETY: Arguments are valid for API. Error is propagated on failure.
+    let res = unsafe { bindings::sysfs_emit(buf, fmt, field(&info.limits)) };
+    #[allow(clippy::as_conversions, reason = "kernel returned value")]
+    let res = res as isize;
+    res
+}
+
+/// Sets the power limit to the value written to `buf` (of `count` bytes) with
+/// `set`, for the chip of the misc device `dev`.
+///
+/// # Safety
+///
+/// `dev` must be a registered misc device created by [`TtMisc::register`], and
+/// `buf` the buffer of `count` bytes of an attribute callback.
+unsafe fn store_power_limit(
+    dev: *mut device,
+    buf: *const ::kernel::ffi::c_char,
+    count: usize,
+    set: fn(&mut PowerLimits, u32),
+) -> isize {
+    // SAFETY: By the safety requirements, `buf` is valid for reads of `count`
+    // bytes.
+    let input = unsafe { ::core::slice::from_raw_parts(buf.cast::<u8>(), count) };
+    let input = input.strip_suffix(b"\n").unwrap_or(input);
+    // SAFETY: By the safety requirements, `dev` is a registered misc device.
+    let node = unsafe { MiscNode::of_device(dev) }.ok_or(ENODEV);
+    let res = node.and_then(|node| {
+        let value = u32::from_str(BStr::from_bytes(input))?;
+        node.chip.check_alive()?;
+        node.chip.update_power_limits(|limits| set(limits, value))
+    });
+    match res {
+        Ok(()) => isize::try_from(count).unwrap_or(isize::MAX),
+        Err(err) => {
+            #[allow(clippy::as_conversions, reason = "kernel error value")]
+            let errno = err.to_errno() as isize;
+            errno
+        },
+    }
+}
+
+/// The `aiclk_min` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows or sets the minimum AI clock of the chip in MHz (see
+/// [`uapi::PowerLimits`]). Fails with `EOPNOTSUPP` if the board is not known,
+/// with `ERANGE` if the value is not within the bounds of the board, and with
+/// `EINVAL` if it exceeds `aiclk_max`.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_AICLK_MIN: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_power_limit(dev, buf, |limits| limits.aiclk_min) }
+    }
+
+    extern "C" fn store(
+        dev: *mut device,
+        _attr: *mut device_attribute,
+        buf: *const ::kernel::ffi::c_char,
+        count: usize,
+    ) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { store_power_limit(dev, buf, count, |limits, value| limits.aiclk_min = value) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"aiclk_min".as_ptr().cast(),
+            mode: 0o644,
+        },
+        show: Some(show),
+        store: Some(store),
+    })
+};
+
+/// The `aiclk_max` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows or sets the maximum AI clock of the chip in MHz (see
+/// [`uapi::PowerLimits`]). Fails with `EOPNOTSUPP` if the board is not known,
+/// with `ERANGE` if the value is not within the bounds of the board, and with
+/// `EINVAL` if it is below `aiclk_min`.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_AICLK_MAX: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_power_limit(dev, buf, |limits| limits.aiclk_max) }
+    }
+
+    extern "C" fn store(
+        dev: *mut device,
+        _attr: *mut device_attribute,
+        buf: *const ::kernel::ffi::c_char,
+        count: usize,
+    ) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { store_power_limit(dev, buf, count, |limits, value| limits.aiclk_max = value) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"aiclk_max".as_ptr().cast(),
+            mode: 0o644,
+        },
+        show: Some(show),
+        store: Some(store),
+    })
+};
+
+/// The `power_limit` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows or sets the power limit of the chip in watts (see
+/// [`uapi::PowerLimits`]). Fails with `EOPNOTSUPP` if the board is not known,
+/// and with `ERANGE` if the value is not within the bounds of the board.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_POWER_LIMIT: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_power_limit(dev, buf, |limits| limits.power_limit) }
+    }
+
+    extern "C" fn store(
+        dev: *mut device,
+        _attr: *mut device_attribute,
+        buf: *const ::kernel::ffi::c_char,
+        count: usize,
+    ) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { store_power_limit(dev, buf, count, |limits, value| limits.power_limit = value) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"power_limit".as_ptr().cast(),
+            mode: 0o644,
+        },
+        show: Some(show),
+        store: Some(store),
+    })
+};
+
+/// The `thermal_limit` attribute for `/sys/class/misc/tenstorrent!N`.
+///
+/// Shows or sets the temperature from which the chip is throttled in degrees
+/// Celsius (see [`uapi::PowerLimits`]). Fails with `EOPNOTSUPP` if the board is
+/// not known, and with `ERANGE` if the value is not within the bounds of the
+/// board.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_THERMAL_LIMIT: Opaque<device_attribute> = {
+    extern "C" fn show(dev: *mut device, _attr: *mut device_attribute, buf: *mut ::kernel::ffi::c_char) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { show_power_limit(dev, buf, |limits| limits.thermal_limit) }
+    }
+
+    extern "C" fn store(
+        dev: *mut device,
+        _attr: *mut device_attribute,
+        buf: *const ::kernel::ffi::c_char,
+        count: usize,
+    ) -> isize {
+        // SAFETY: `dev` and `buf` are valid for the duration of the callback.
+        unsafe { store_power_limit(dev, buf, count, |limits, value| limits.thermal_limit = value) }
+    }
+
+    Opaque::new(device_attribute {
+        attr: attribute {
+            name: c"thermal_limit".as_ptr().cast(),
+            mode: 0o644,
+        },
+        show: Some(show),
+        store: Some(store),
+    })
+};
+
+/// Returns the `attribute` of the device attribute `attr`.
+const fn attr_of(attr: *const Opaque<device_attribute>) -> *mut attribute {
+    let attr = Opaque::raw_get(attr);
+    // SAFETY: `attr` points at a device attribute.
+    unsafe { &raw mut (*attr).attr }
+}
+
+/// The attributes for `/sys/class/misc/tenstorrent!N`.
+const ATTRS: [*mut attribute; 12] = [
+    attr_of(&raw const ATTR_PCI_BDF),
+    attr_of(&raw const ATTR_STATE_GENERATION),
+    attr_of(&raw const ATTR_AER_COUNTS),
+    attr_of(&raw const ATTR_ETH_COORD),
+    attr_of(&raw const ATTR_ETH_LINKS),
+    attr_of(&raw const ATTR_HARVESTING),
+    attr_of(&raw const ATTR_FIRMWARE_VERSIONS),
+    attr_of(&raw const ATTR_AICLK_MIN),
+    attr_of(&raw const ATTR_AICLK_MAX),
+    attr_of(&raw const ATTR_POWER_LIMIT),
+    attr_of(&raw const ATTR_THERMAL_LIMIT),
+    ::core::ptr::null_mut(),
+];
+
+/// The attribute group for `/sys/class/misc/tenstorrent!N`.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut ATTR_GROUP: Opaque<attribute_group> = Opaque::new(attribute_group {
+    attrs: ATTRS.as_ptr().cast_mut(),
+    // SAFETY: All-zeroes is a valid bit pattern for the remaining fields.
+    ..unsafe { MaybeUninit::zeroed().assume_init() }
+});
+
+/// The attribute groups for `/sys/class/misc/tenstorrent!N`.
+const GROUP_PTRS: [*const attribute_group; 2] =
+    [Opaque::raw_get(&raw const ATTR_GROUP).cast_const(), ::core::ptr::null()];
+
+/// The attribute groups of the misc devices (see [`TtMisc::register`]).
+static GROUPS: AttributeGroups = {
+    // SAFETY: `GROUP_PTRS` is null-terminated and points at a static group
+    // which is never modified.
+    unsafe { AttributeGroups::new(&GROUP_PTRS) }
+};
+
 #[pinned_drop]
 impl PinnedDrop for TtMisc {
-    fn drop(self: Pin<&mut Self>) {}
+    fn drop(self: Pin<&mut Self>) {
+        if self.busy.load(Relaxed) != 0 {
+            if let Err(err) = self.chip.hint_busy(&self.busy, false) {
+                pr_warn!("(power): cannot enter low-power state: {err:?}\n");
+            }
+        }
+        self.chip.events().unbind_file(self.file_id);
+        self.chip.remove_file(self.file_id);
+    }
 }
 
 #[allow(clippy::module_inception, reason = "style")]
 mod misc {
+    use ::kernel::str::CStr;
+
     /// A wrapper for a misc ordinal which frees it from the pool on drop.
     pub(crate) struct MiscOrdinal {
         /// The value of the ordinal.
         value: usize,
+        /// The devfs name associated with the ordinal.
+        name: &'static CStr,
     }
 
     impl MiscOrdinal {
         /// Creates a [`MiscOrdinal`].
-        pub(crate) const fn new(value: usize) -> Self {
-            Self { value }
+        pub(crate) const fn new(value: usize, name: &'static CStr) -> Self {
+            Self { value, name }
+        }
+
+        /// Returns the value of the ordinal.
+        pub(crate) const fn value(&self) -> usize {
+            self.value
+        }
+
+        /// Returns the devfs name associated with the ordinal.
+        pub(crate) const fn name(&self) -> &'static CStr {
+            self.name
         }
     }
 
     impl Drop for MiscOrdinal {
         fn drop(&mut self) {
             let ordinal = self.value;
-            ::kernel::pr_info!("freeing ordinal: {ordinal}\n");
+            crate::tt::trace::ordinal_free(ordinal);
             super::supply::MISC_SUPPLY.free(ordinal);
         }
     }
@@ -121,6 +1206,13 @@ mod supply {
             }
         }
 
+        /// Calls `f` with each assigned ordinal and its devfs name.
+        pub(super) fn for_each<F: FnMut(usize, &CStr)>(&self, f: F) {
+            if let Some(pool) = self.once.as_ref() {
+                pool.for_each(f);
+            }
+        }
+
         /// Returns the next misc device ordinal and the associated devfs name.
         ///
         /// # Errors
@@ -129,7 +1221,8 @@ mod supply {
         /// * Errors if updating the [`SetOnce`] fails.
         pub(super) fn next(&self) -> Result<(MiscOrdinal, &'static CStr)> {
             self.init()?;
-            self.once.as_ref().ok_or(ENOMEM)?.next()
+            let (ordinal, name) = self.once.as_ref().ok_or(ENOMEM)?.next()?;
+            Ok((MiscOrdinal::new(ordinal, name), name))
         }
 
         /// Initialize the misc supply if not already initialized.
@@ -158,20 +1251,17 @@ mod supply {
 mod private {
     #![allow(clippy::impl_trait_in_params, reason = "pin_data macro")]
 
-    use ::core::num::NonZero;
     use ::kernel::{
         prelude::*,
         str::CString,
         xarray::{self, XArray},
     };
 
-    use crate::tt::device::misc::MiscOrdinal;
-
     /// A pool for assignment misc device ordinals and devfs names.
     #[pin_data]
     pub(super) struct MiscPool {
-        /// The maximum number of ordinals to choose from.
-        limit: NonZero<u32>,
+        /// The number of ordinals to choose from.
+        limit: u32,
         /// The underlying [XArray] providing the pool API.
         #[pin]
         names: XArray<KBox<CString>>,
@@ -179,10 +1269,7 @@ mod private {
 
     impl MiscPool {
         /// Creates a pool for assigning misc device ordinals and devfs names.
-        pub(super) fn new(mut limit: u32) -> impl PinInit<Self> {
-            limit += 1;
-            // SAFETY: Value of `max + 1` is non-zero by definition.
-            let limit = unsafe { NonZero::new_unchecked(limit) };
+        pub(super) fn new(limit: u32) -> impl PinInit<Self> {
             pin_init!(Self {
                 limit,
                 names <- XArray::new(xarray::AllocKind::Alloc),
@@ -193,15 +1280,22 @@ mod private {
         ///
         /// # Errors
         ///
-        /// * Errors if reserving an ordinal fails (if all are exhausted).
+        /// * Errors with `ENOSPC` if all ordinals are assigned.
+        /// * Errors if reserving an ordinal fails.
         /// * Errors if formatting the name from the ordinal fails.
         /// * Errors if allocating the name for the reservation fails.
         /// * Errors if filling the reservation fails.
         /// * Errors if looking up the [`&CStr`] from the [`XArray`] fails.
-        pub(super) fn next(&self) -> Result<(MiscOrdinal, &'static CStr)> {
+        pub(super) fn next(&self) -> Result<(usize, &'static CStr)> {
+            if self.limit == 0 {
+                return Err(ENOSPC);
+            }
+
             // Find the next available reservation.
             let mut guard = self.names.lock();
-            let res = guard.reserve_limit(.. self.limit.get(), GFP_KERNEL)?;
+            let res = guard
+                .reserve_limit(.. self.limit, GFP_KERNEL)
+                .map_err(|err| if err == EBUSY { ENOSPC } else { err })?;
 
             // Compute the prefix and get the ordinal from the reservation.
             let prefix = crate::tt::device::misc::TtMisc::DEVICE_PREFIX;
@@ -214,8 +1308,8 @@ mod private {
 
             // Construct the [&'static CStr] from the computed name.
             let data = guard.get(ordinal).ok_or(ERANGE)?.as_char_ptr();
-            let ordinal = MiscOrdinal::new(ordinal);
-            // SAFETY: names are never dropped until module is unloaded.
+            // SAFETY: A name is only dropped when its ordinal is freed, after
+            // which the users of the ordinal no longer refer to it.
             let name = unsafe { CStr::from_char_ptr(data) };
 
             // Return the obtained ordinal and computed name.
@@ -226,5 +1320,212 @@ mod private {
         pub(super) fn free(&self, ordinal: usize) {
             self.names.lock().remove(ordinal);
         }
+
+        /// Calls `f` with each assigned ordinal and its devfs name.
+        ///
+        /// NOTE: `f` is called with the pool locked, so it must not sleep.
+        pub(super) fn for_each<F: FnMut(usize, &CStr)>(&self, mut f: F) {
+            let guard = self.names.lock();
+            let Ok(limit) = usize::try_from(self.limit) else {
+                return;
+            };
+            for ordinal in 0 .. limit {
+                if let Some(name) = guard.get(ordinal) {
+                    f(ordinal, name);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(CONFIG_KUNIT)]
+#[::kernel::macros::kunit_tests(tt_core_misc)]
+mod tests {
+    use ::kernel::{
+        prelude::*,
+        str::CString,
+        sync::{
+            Arc,
+            Completion,
+            atomic::{Atomic, Relaxed},
+        },
+        workqueue::{self, Work, WorkItem},
+    };
+
+    use super::{TtMisc, private::MiscPool, supply::MISC_SUPPLY};
+
+    /// The number of ordinals of the pool shared by [`Churn`] workers.
+    const CHURN_LIMIT: usize = 4;
+    /// The number of [`Churn`] workers.
+    const CHURN_WORKERS: usize = 8;
+    /// The number of ordinals each [`Churn`] worker takes and frees.
+    const CHURN_ITERATIONS: usize = 1000;
+
+    /// Creates a pool of `limit` ordinals.
+    fn pool(limit: u32) -> Result<Arc<MiscPool>> {
+        Arc::pin_init(MiscPool::new(limit), GFP_KERNEL)
+    }
+
+    /// Returns `true` if `name` is the devfs name of `ordinal`.
+    fn is_name_of(ordinal: usize, name: &CStr) -> bool {
+        let prefix = TtMisc::DEVICE_PREFIX;
+        CString::try_from_fmt(fmt!("{prefix}/{ordinal}")).is_ok_and(|expected| expected.to_bytes() == name.to_bytes())
+    }
+
+    /// The state shared by [`Churn`] workers.
+    struct Shared {
+        /// The pool.
+        pool: Arc<MiscPool>,
+        /// Whether each ordinal is currently held by a worker.
+        held: [Atomic<u32>; CHURN_LIMIT],
+        /// The number of failed or inconsistent assignments.
+        failures: Atomic<u32>,
+    }
+
+    /// A worker which repeatedly takes and frees ordinals of a shared pool.
+    #[pin_data]
+    struct Churn {
+        /// The shared state.
+        shared: Arc<Shared>,
+        /// The work item.
+        #[pin]
+        work: Work<Churn>,
+        /// Completed once the worker has finished.
+        #[pin]
+        done: Completion,
+    }
+
+    ::kernel::impl_has_work! {
+        impl HasWork<Self> for Churn { self.work }
+    }
+
+    impl WorkItem for Churn {
+        type Pointer = Arc<Self>;
+
+        fn run(this: Arc<Self>) {
+            let shared = &this.shared;
+            for _iteration in 0 .. CHURN_ITERATIONS {
+                let Ok((ordinal, name)) = shared.pool.next() else {
+                    shared.failures.add(1, Relaxed);
+                    continue;
+                };
+                let unique = shared.held.get(ordinal).is_some_and(|held| held.xchg(1, Relaxed) == 0);
+                if !unique || !is_name_of(ordinal, name) {
+                    shared.failures.add(1, Relaxed);
+                }
+                if let Some(held) = shared.held.get(ordinal) {
+                    held.store(0, Relaxed);
+                }
+                shared.pool.free(ordinal);
+            }
+            this.done.complete_all();
+        }
+    }
+
+    impl Churn {
+        /// Creates a worker for `shared`.
+        fn new(shared: &Arc<Shared>) -> Result<Arc<Self>> {
+            Arc::pin_init(
+                pin_init!(Self {
+                    shared: Arc::clone(shared),
+                    work <- ::kernel::new_work!("Churn::work"),
+                    done <- Completion::new(),
+                }),
+                GFP_KERNEL,
+            )
+        }
+    }
+
+    #[test]
+    fn exhaustion() -> Result {
+        let pool = pool(4)?;
+        for expected in 0 .. 4 {
+            let (ordinal, _name) = pool.next()?;
+            assert_eq!(ordinal, expected);
+        }
+        assert_eq!(pool.next().err(), Some(ENOSPC));
+
+        let empty = self::pool(0)?;
+        assert_eq!(empty.next().err(), Some(ENOSPC));
+        Ok(())
+    }
+
+    #[test]
+    fn reuse() -> Result {
+        let pool = pool(4)?;
+        for _ordinal in 0 .. 4 {
+            pool.next()?;
+        }
+        pool.free(1);
+        pool.free(2);
+        assert_eq!(pool.next()?.0, 1);
+        assert_eq!(pool.next()?.0, 2);
+        assert_eq!(pool.next().err(), Some(ENOSPC));
+        Ok(())
+    }
+
+    #[test]
+    fn names() -> Result {
+        let pool = pool(4)?;
+        let (first, name) = pool.next()?;
+        assert!(is_name_of(first, name));
+        let (second, name) = pool.next()?;
+        assert!(is_name_of(second, name));
+
+        pool.free(first);
+        let (again, name) = pool.next()?;
+        assert_eq!(again, first);
+        assert!(is_name_of(again, name));
+
+        let mut count = 0;
+        let mut consistent = true;
+        pool.for_each(|ordinal, name| {
+            count += 1;
+            consistent &= is_name_of(ordinal, name);
+        });
+        assert_eq!(count, 2);
+        assert!(consistent);
+        Ok(())
+    }
+
+    #[test]
+    fn supply_frees_on_drop() -> Result {
+        let (ordinal, name) = MISC_SUPPLY.next()?;
+        let value = ordinal.value();
+        assert!(is_name_of(value, name));
+        drop(ordinal);
+
+        let (ordinal, name) = MISC_SUPPLY.next()?;
+        assert_eq!(ordinal.value(), value);
+        assert!(is_name_of(value, name));
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent() -> Result {
+        let shared = Arc::new(
+            Shared {
+                pool: pool(u32::try_from(CHURN_LIMIT)?)?,
+                held: [const { Atomic::new(0u32) }; CHURN_LIMIT],
+                failures: Atomic::new(0u32),
+            },
+            GFP_KERNEL,
+        )?;
+
+        let mut churns = KVec::with_capacity(CHURN_WORKERS, GFP_KERNEL)?;
+        for _worker in 0 .. CHURN_WORKERS {
+            let churn = Churn::new(&shared)?;
+            churns.push(Arc::clone(&churn), GFP_KERNEL)?;
+            workqueue::system_unbound().enqueue(churn).map_err(|_churn| EBUSY)?;
+        }
+        for churn in &churns {
+            churn.done.wait_for_completion();
+        }
+
+        assert_eq!(shared.failures.load(Relaxed), 0);
+        let mut remaining = 0;
+        shared.pool.for_each(|_ordinal, _name| remaining += 1);
+        assert_eq!(remaining, 0);
+        Ok(())
     }
 }
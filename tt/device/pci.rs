@@ -1,52 +1,188 @@
 // SPDX-License-Identifier: GPL-2.0
 
-use ::kernel::prelude::*;
+use ::kernel::{
+    bindings,
+    pci::{ChannelState, ErsResult},
+    prelude::*,
+    sync::Arc,
+};
 
-use crate::tt::device::misc::{MiscOrdinal, TtMisc};
+use crate::tt::{
+    compat::CompatDevice,
+    debugfs::DeviceDir,
+    device::{
+        chip::TtChip,
+        irq::TtIrqs,
+        misc::{MiscNode, MiscOrdinal, TtMisc},
+        watchdog::TtWatchdog,
+    },
+    reboot::RebootEntry,
+};
 
 // The PCI device table.
+//
+// NOTE: The fallback configuration must remain the first entry (see
+// [`crate::tt::hw::HWCONFIG_FALLBACK`]).
 ::kernel::pci_device_table!(PCI_TABLE, MODULE_PCI_TABLE, <TtPci as ::kernel::pci::Driver>::IdInfo, [
+    crate::tt::hw::HWCONFIG_FALLBACK.pci_device_table_item(),
     crate::tt::hw::HWCONFIG_BLACKHOLE.pci_device_table_item(),
     crate::tt::hw::HWCONFIG_GRAYSKULL.pci_device_table_item(),
     crate::tt::hw::HWCONFIG_WORMHOLE.pci_device_table_item(),
 ]);
 
 /// The PCI device.
-#[pin_data]
+#[pin_data(PinnedDrop)]
 pub struct TtPci {
+    /// The per-chip state.
+    chip: Arc<TtChip>,
+    /// The heartbeat watchdog.
+    #[pin]
+    watchdog: TtWatchdog,
+    /// The interrupt vectors and their handlers.
+    irqs: TtIrqs,
+    /// The registration with the reboot notifier.
+    reboot: RebootEntry,
+    /// The debugfs directory.
+    debugfs: DeviceDir,
+    /// The registration with the compatibility overlay.
+    compat: CompatDevice,
+    /// The misc device.
+    #[pin]
+    misc: MiscNode,
     /// The ordinal for the misc device.
+    ///
+    /// NOTE: Declared after `misc` so that the name (freed along with
+    /// the ordinal) outlives the registration.
     ordinal: MiscOrdinal,
-    /// The registration for the misc device.
-    #[pin]
-    misc_dev_reg: ::kernel::miscdevice::MiscDeviceRegistration<TtMisc>,
 }
 
 impl ::kernel::pci::Driver for TtPci {
     type IdInfo = crate::tt::hw::HwConfig;
 
+    const HAS_ERR_HANDLER: bool = true;
     const ID_TABLE: ::kernel::pci::IdTable<Self::IdInfo> = &PCI_TABLE;
 
     fn probe(dev: &::kernel::pci::Device<::kernel::device::Core>, id_info: &Self::IdInfo) -> Result<Pin<KBox<Self>>> {
-        pr_info!(
-            "(probe): vendor_id={:#06X}, device_id={:#06X}, name={}\n",
+        let hw = *id_info;
+        let board = hw.board(subsystem_device_id(dev));
+
+        let parent: &::kernel::device::Device<::kernel::device::Core> = dev.as_ref();
+        let parent: &::kernel::device::Device = parent;
+        let (ordinal, init) = TtMisc::register(Some(parent))?;
+        crate::tt::trace::probe(
+            ordinal.value(),
             dev.vendor_id(),
             dev.device_id(),
-            id_info.name,
+            hw.name,
+            board.map_or(::kernel::c_str!("unknown"), |board| board.name),
         );
 
-        dev.enable_device()?;
-        dev.set_master();
+        if hw.is_identify_only() {
+            pr_warn!("(probe): unknown device; binding in identify-only mode\n");
+        } else {
+            dev.enable_device()?;
+            dev.set_master();
+            TtChip::save_state(dev)?;
+        }
 
-        let (ordinal, init) = TtMisc::register()?;
+        let chip = TtChip::new(dev, hw, board, ordinal.value())?;
+        chip.read_firmware_info();
+        chip.load_firmware(parent);
+        let irqs = TtIrqs::new(dev, &chip)?;
+        let reboot = RebootEntry::new(&chip)?;
+        // SAFETY: `parent` is valid and its name is set while it is registered.
+        let name = unsafe { CStr::from_char_ptr((*parent.as_raw()).kobj.name) };
+        let debugfs = DeviceDir::new(name, &chip)?;
+        let compat = CompatDevice::new(dev, ordinal.name(), &chip)?;
 
         let init = try_pin_init!(Self {
+            misc <- MiscNode::new(&chip, init),
+            watchdog <- TtWatchdog::new(dev, &chip),
+            chip,
+            irqs,
+            reboot,
+            debugfs,
+            compat,
             ordinal,
-            misc_dev_reg <- init,
         });
         let this = KBox::pin_init(init, GFP_KERNEL)?;
+        this.watchdog.start();
 
-        this.misc_dev_reg.device().pr_info(fmt!("registered"));
+        this.misc.registration().device().pr_info(fmt!("registered"));
 
         Ok(this)
     }
+
+    fn suspend(this: Pin<&Self>, dev: &::kernel::pci::Device<::kernel::device::Core>) -> Result {
+        this.watchdog.stop();
+        let res = this.chip.suspend(dev);
+        if res.is_err() {
+            // The chip stays awake, so keep watching it.
+            this.watchdog.start();
+        }
+        res
+    }
+
+    fn resume(this: Pin<&Self>, dev: &::kernel::pci::Device<::kernel::device::Core>) -> Result {
+        this.chip.resume(dev);
+        TtMisc::notify_state_lost(this.misc.registration());
+        this.watchdog.start();
+        Ok(())
+    }
+
+    fn shutdown(this: Pin<&Self>, dev: &::kernel::pci::Device<::kernel::device::Core>) {
+        this.watchdog.stop();
+        this.chip.shutdown(Some(dev));
+    }
+
+    fn error_detected(
+        this: Pin<&Self>,
+        _dev: &::kernel::pci::Device<::kernel::device::Core>,
+        state: ChannelState,
+    ) -> ErsResult {
+        pr_err!("(error_detected): state={state:?}\n");
+        let lost = state != ChannelState::Normal;
+        let vote = this.chip.error_detected(state);
+        if lost {
+            TtMisc::notify_state_lost(this.misc.registration());
+        }
+        vote
+    }
+
+    fn mmio_enabled(this: Pin<&Self>, dev: &::kernel::pci::Device<::kernel::device::Core>) -> ErsResult {
+        this.chip.mmio_enabled(dev)
+    }
+
+    fn slot_reset(this: Pin<&Self>, dev: &::kernel::pci::Device<::kernel::device::Core>) -> ErsResult {
+        this.chip.slot_reset(dev)
+    }
+
+    fn error_resume(this: Pin<&Self>, _dev: &::kernel::pci::Device<::kernel::device::Core>) {
+        this.chip.error_resume();
+        pr_info!("(error_resume): recovered\n");
+    }
+}
+
+#[pinned_drop]
+impl PinnedDrop for TtPci {
+    fn drop(self: Pin<&mut Self>) {
+        // The limits are only kept while the driver is bound.
+        self.chip.reset_power();
+        // Open files may outlive the device, so tell them it is gone.
+        self.chip.remove();
+    }
+}
+
+/// Returns the raw `struct pci_dev` of `dev`.
+pub(crate) fn raw_pci_dev(dev: &::kernel::pci::Device) -> *mut bindings::pci_dev {
+    let dev = AsRef::<::kernel::device::Device>::as_ref(dev).as_raw();
+    // SAFETY: `dev` is embedded in a valid `struct pci_dev`.
+    unsafe { ::kernel::container_of!(dev, bindings::pci_dev, dev) }
+}
+
+/// Returns the PCI subsystem device ID of `dev`.
+fn subsystem_device_id(dev: &::kernel::pci::Device) -> u16 {
+    let pdev = raw_pci_dev(dev);
+    // SAFETY: `pdev` is valid for reads since `dev` is valid.
+    unsafe { (*pdev).subsystem_device }
 }
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: GPL-2.0
+
+/*
+ * Delayed work of the heartbeat watchdog.
+ *
+ * Delayed work is not part of the Rust workqueue abstractions of 6.16, and
+ * INIT_DELAYED_WORK() and queue_delayed_work() are macros and inline
+ * functions, so they are wrapped here and called from Rust (see watchdog.rs).
+ */
+
+#include <linux/workqueue.h>
+
+void tt_watchdog_init(struct delayed_work *dwork, work_func_t func);
+bool tt_watchdog_queue(struct delayed_work *dwork, unsigned long delay);
+
+void tt_watchdog_init(struct delayed_work *dwork, work_func_t func)
+{
+	INIT_DELAYED_WORK(dwork, func);
+}
+
+bool tt_watchdog_queue(struct delayed_work *dwork, unsigned long delay)
+{
+	return queue_delayed_work(system_wq, dwork, delay);
+}
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: The delayed work is initialized and queued by the C wrappers in
+// `watchdog.c`, since delayed work is not part of the Rust workqueue
+// abstractions of 6.16.
+
+use ::kernel::{
+    bindings,
+    ffi::c_ulong,
+    pci,
+    prelude::*,
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{Atomic, Relaxed},
+    },
+    time::{Delta, msecs_to_jiffies},
+    types::{ARef, Opaque},
+};
+
+use crate::tt::device::{chip::TtChip, coredump::Reason, event::EventKind};
+
+unsafe extern "C" {
+    /// Initializes `dwork` to run `func`.
+    fn tt_watchdog_init(dwork: *mut bindings::delayed_work, func: bindings::work_func_t);
+    /// Queues `dwork` on the system workqueue after `delay` jiffies.
+    fn tt_watchdog_queue(dwork: *mut bindings::delayed_work, delay: c_ulong) -> bool;
+}
+
+/// The interval between checks of the heartbeat in milliseconds.
+const CHECK_INTERVAL_MS: u32 = 1000;
+
+/// The heartbeat watchdog of a chip.
+///
+/// A delayed work item reads the heartbeat and the throttler of the firmware
+/// (see [`crate::tt::hw::telemetry::TelemetryLayout`]) every
+/// [`CHECK_INTERVAL_MS`]. When the throttler becomes nonzero, open files are
+/// sent a `ThermalThrottle` event. If it does not change for `heartbeat_timeout` seconds,
+/// the chip is marked as hung (failing further use by open files), open files
+/// are sent a `FirmwareHung` event, and a coredump is captured. With
+/// `heartbeat_auto_reset` set, the chip is then reset (see
+/// [`TtChip::recover`]), retrying at each check while the device lock is held
+/// elsewhere. The chip is no longer hung once it is reset or its heartbeat
+/// changes again.
+///
+/// The heartbeat is only checked for chips whose telemetry has one, and with
+/// `heartbeat_timeout` set. The watchdog only runs for chips whose heartbeat
+/// or throttler is checked, and not while the chip has failed, is shut down or
+/// suspended (see [`TtWatchdog::stop`]).
+#[pin_data(PinnedDrop)]
+pub(crate) struct TtWatchdog {
+    /// The delayed work item.
+    #[pin]
+    dwork: Opaque<bindings::delayed_work>,
+    /// Whether the work item requeues itself.
+    running: Atomic<u32>,
+    /// The state of the chip.
+    chip: Arc<TtChip>,
+    /// The PCI device (for coredumps and resets).
+    pdev: ARef<pci::Device>,
+    /// The telemetry last seen.
+    #[pin]
+    state: Mutex<Watched>,
+}
+
+/// The telemetry of a chip, as last seen by its [`TtWatchdog`].
+struct Watched {
+    /// The BAR0 offset of the heartbeat, once reported by the firmware.
+    offset: Option<usize>,
+    /// The value of the heartbeat, once read.
+    value: Option<u32>,
+    /// The time the value last changed (`CLOCK_MONOTONIC`) in nanoseconds.
+    changed_ns: i64,
+    /// Whether the hung chip is still to be reset, as the device lock was
+    /// held at the last check.
+    reset_pending: bool,
+    /// The BAR0 offset of the throttler, once reported by the firmware.
+    throttler: Option<usize>,
+    /// Whether the firmware was throttling the chip.
+    throttled: bool,
+}
+
+impl Watched {
+    /// Forgets the telemetry, as after a reset (which may move the telemetry
+    /// table) or a pause of the watchdog.
+    fn forget(&mut self, now: i64) {
+        self.offset = None;
+        self.value = None;
+        self.changed_ns = now;
+        self.reset_pending = false;
+        self.throttler = None;
+        self.throttled = false;
+    }
+}
+
+#[pinned_drop]
+impl PinnedDrop for TtWatchdog {
+    fn drop(self: Pin<&mut Self>) {
+        self.stop();
+    }
+}
+
+impl TtWatchdog {
+    /// Creates the (stopped) watchdog of `chip` (of the PCI device `dev`).
+    pub(crate) fn new(dev: &pci::Device, chip: &Arc<TtChip>) -> impl PinInit<Self> {
+        pin_init!(Self {
+            // SAFETY: `tt_watchdog_init` initializes the delayed work in place.
+            dwork <- Opaque::ffi_init(|slot| unsafe { tt_watchdog_init(slot, Some(Self::run)) }),
+            running: Atomic::new(0u32),
+            chip: Arc::clone(chip),
+            pdev: dev.into(),
+            state <- ::kernel::new_mutex!(Watched {
+                offset: None,
+                value: None,
+                changed_ns: 0,
+                reset_pending: false,
+                throttler: None,
+                throttled: false,
+            }),
+        })
+    }
+
+    /// Returns `true` if the heartbeat of the chip is checked.
+    fn checks_heartbeat(&self) -> bool {
+        let has_heartbeat = self
+            .chip
+            .hw()
+            .telemetry
+            .is_some_and(|layout| layout.heartbeat.is_some());
+        has_heartbeat && *crate::module_parameters::heartbeat_timeout.value() != 0
+    }
+
+    /// Returns `true` if the throttler of the chip is checked.
+    fn checks_throttler(&self) -> bool {
+        self.chip
+            .hw()
+            .telemetry
+            .is_some_and(|layout| layout.throttler.is_some())
+    }
+
+    /// Returns `true` if the watchdog is enabled for the chip.
+    fn enabled(&self) -> bool {
+        self.checks_heartbeat() || self.checks_throttler()
+    }
+
+    /// Starts (or restarts) the watchdog, unless disabled.
+    pub(crate) fn start(&self) {
+        if !self.enabled() {
+            return;
+        }
+        // SAFETY: FFI call without safety requirements.
+        let now = unsafe { bindings::ktime_get() };
+        self.state.lock().forget(now);
+        if self.running.xchg(1, Relaxed) == 0 {
+            self.queue();
+        }
+    }
+
+    /// Stops the watchdog, waiting for a running check to finish.
+    pub(crate) fn stop(&self) {
+        self.running.store(0, Relaxed);
+        // SAFETY: The delayed work was initialized in `TtWatchdog::new`.
+        unsafe { bindings::cancel_delayed_work_sync(self.dwork.get()) };
+    }
+
+    /// Queues the next check.
+    fn queue(&self) {
+        let delay = msecs_to_jiffies(CHECK_INTERVAL_MS);
+        // SAFETY: The delayed work was initialized in `TtWatchdog::new`.
+        unsafe { tt_watchdog_queue(self.dwork.get(), delay) };
+    }
+
+    /// Runs a check and queues the next one (unless stopped).
+    extern "C" fn run(work: *mut bindings::work_struct) {
+        // SAFETY: `work` is the work of the delayed work of a `TtWatchdog`.
+        let dwork = unsafe { ::kernel::container_of!(work, bindings::delayed_work, work) };
+        // SAFETY: `dwork` is the delayed work of a `TtWatchdog` (which is
+        // `Opaque` and thus has the same layout), which is alive until the
+        // work is cancelled on drop.
+        let this = unsafe { &*::kernel::container_of!(dwork.cast::<Opaque<bindings::delayed_work>>(), Self, dwork) };
+        this.check();
+        if this.running.load(Relaxed) != 0 {
+            this.queue();
+        }
+    }
+
+    /// Checks the throttler (see [`TtWatchdog::check_throttler`]) and the
+    /// heartbeat, handling the chip as hung if it stopped for
+    /// `heartbeat_timeout` seconds, and as no longer hung if it changes again.
+    fn check(&self) {
+        let chip = &*self.chip;
+        // SAFETY: FFI call without safety requirements.
+        let now = unsafe { bindings::ktime_get() };
+        let mut state = self.state.lock();
+        if chip.is_failed() || chip.is_shut_down() {
+            state.forget(now);
+            return;
+        }
+        if self.checks_throttler() {
+            self.check_throttler(&mut state);
+        }
+        if !self.checks_heartbeat() {
+            return;
+        }
+
+        let beat = state
+            .offset
+            .map_or_else(|| chip.heartbeat_offset(), Ok)
+            .and_then(|offset| Ok((offset, chip.read_reg(offset)?)));
+        match beat {
+            Ok((offset, value)) => {
+                state.offset = Some(offset);
+                if state.value != Some(value) {
+                    state.value = Some(value);
+                    state.changed_ns = now;
+                    state.reset_pending = false;
+                    if chip.unhang() {
+                        pr_info!("(watchdog): firmware heartbeat resumed\n");
+                    }
+                    return;
+                }
+            },
+            // A heartbeat which cannot be read counts as stopped.
+            Err(_) => state.offset = None,
+        }
+
+        let pending = state.reset_pending;
+        if !pending {
+            let timeout = Delta::from_secs(i64::from(*crate::module_parameters::heartbeat_timeout.value()));
+            let stalled = Delta::from_nanos(now - state.changed_ns);
+            if stalled < timeout || !chip.hang(stalled) {
+                return;
+            }
+        }
+        drop(state);
+
+        if !pending {
+            chip.coredump(&self.pdev, Reason::HeartbeatStopped);
+            if *crate::module_parameters::heartbeat_auto_reset.value() == 0 {
+                return;
+            }
+        }
+
+        // The device lock is only tried (see `TtChip::recover`), as unbind,
+        // suspend and shutdown hold it while waiting for this check in
+        // `TtWatchdog::stop`.
+        let res = chip.recover(&self.pdev);
+        // SAFETY: FFI call without safety requirements.
+        let now = unsafe { bindings::ktime_get() };
+        let mut state = self.state.lock();
+        match res {
+            Err(err) if err == EAGAIN => state.reset_pending = true,
+            res => {
+                if let Err(err) = res {
+                    pr_err!("(watchdog): chip did not recover: {err:?}\n");
+                }
+                state.forget(now);
+            },
+        }
+    }
+
+    /// Reads the throttler, sending open files a `ThermalThrottle` event when
+    /// the firmware starts throttling the chip.
+    fn check_throttler(&self, state: &mut Watched) {
+        let chip = &*self.chip;
+        let word = state
+            .throttler
+            .map_or_else(|| chip.throttler_offset(), Ok)
+            .and_then(|offset| Ok((offset, chip.read_reg(offset)?)));
+        match word {
+            Ok((offset, value)) => {
+                state.throttler = Some(offset);
+                let throttled = value != 0;
+                if throttled && !state.throttled {
+                    pr_info!("(watchdog): firmware throttling chip ({value:#x})\n");
+                    chip.post(EventKind::ThermalThrottle, u64::from(value));
+                }
+                state.throttled = throttled;
+            },
+            // The table is looked up again at the next check.
+            Err(_) => state.throttler = None,
+        }
+    }
+}
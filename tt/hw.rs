@@ -1,13 +1,47 @@
 // SPDX-License-Identifier: GPL-2.0
 
-use ::kernel::prelude::*;
+// NOTE: The hardware tables of the architectures are taken from public sources,
+// which are cited with each table. They have not been verified on every board
+// revision and firmware release.
+
+use ::kernel::{c_str, prelude::*};
+
+use self::{
+    flash::FlashLayout,
+    fw::FwImage,
+    grid::Grid,
+    power::{PowerBounds, PowerStates},
+    telemetry::TelemetryLayout,
+};
+
+/// Definitions related to messaging the ARC management firmware.
+pub(crate) mod arc;
 
 /// Definition related to the Tenstorrent Blackhole hardware.
 pub(crate) mod blackhole;
 
+/// Definitions related to the register model of emulated chips.
+pub(crate) mod emu;
+
+/// Definitions related to the SPI flash of the boards.
+pub(crate) mod flash;
+
+/// Definitions related to the firmware images loaded by the driver.
+pub(crate) mod fw;
+
+/// Definitions related to the NOC grids of the chips.
+pub(crate) mod grid;
+
 /// Definitions related to the Tenstorrent Grayskull hardware.
 pub(crate) mod grayskull;
 
+/// Definitions related to the power and clock limits and the power states of
+/// the chips.
+pub(crate) mod power;
+
+/// Definitions related to the telemetry of the ARC firmware.
+pub(crate) mod telemetry;
+
 /// Definitions related to the Tenstorrent Wormhole hardware.
 pub(crate) mod wormhole;
 
@@ -18,8 +52,103 @@ pub(crate) use wormhole::HWCONFIG_WORMHOLE;
 /// The Tenstorrent PCI vendor string.
 pub(crate) const PCI_VENDOR_ID_TENSTORRENT: u32 = 0x1E52;
 
+/// The size of the BAR0 region mapped by the driver.
+pub(crate) const BAR0_SIZE: usize = 0x2000_0000;
+
+/// The mapping of the BAR0 region.
+pub(crate) type Bar0 = ::kernel::pci::Bar<BAR0_SIZE>;
+
+/// 32-bit access to the registers of a chip.
+///
+/// Implemented by the BAR0 mapping of a PCI device and by the register model of
+/// an emulated chip ([`emu::EmuRegs`]).
+pub(crate) trait RegIo {
+    /// Reads the register at the BAR0 `offset`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if `offset` is out of bounds or misaligned.
+    fn read_reg(&self, offset: usize) -> Result<u32>;
+
+    /// Writes `value` to the register at the BAR0 `offset`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if `offset` is out of bounds or misaligned.
+    fn write_reg(&self, offset: usize, value: u32) -> Result;
+
+    /// Called once the TLB window `tlb` is pointed at `addr` of the core at
+    /// NOC0 coordinates (`x`, `y`), e.g. to trace it. Does nothing by default.
+    fn tlb_configured(&self, _tlb: usize, _x: u8, _y: u8, _addr: u64) {}
+}
+
+impl RegIo for Bar0 {
+    fn read_reg(&self, offset: usize) -> Result<u32> {
+        self.try_read32(offset)
+    }
+
+    fn write_reg(&self, offset: usize, value: u32) -> Result {
+        self.try_write32(value, offset)
+    }
+}
+
+/// The fallback hardware configuration for devices bound through `new_id`.
+///
+/// Dynamic IDs added without explicit `driver_data` resolve to the first entry
+/// of the PCI device table, so this configuration must be listed first. The
+/// device ID `0xFFFF` is not a valid PCI device ID, so the static entry never
+/// matches any hardware by itself. For example:
+///
+/// ```text
+/// echo "1e52 b141" > /sys/bus/pci/drivers/tt_core/new_id
+/// ```
+///
+/// Devices bound with this configuration run in identify-only mode: they are
+/// enumerated and given a misc device but no BAR access features are enabled.
+pub(crate) const HWCONFIG_FALLBACK: HwConfig = HwConfig {
+    vendor_id: PCI_VENDOR_ID_TENSTORRENT,
+    device_id: 0xFFFF,
+    name: c_str!("Unknown"),
+    arch: Arch::Unknown,
+    boards: &[],
+    grid: &Grid::EMPTY,
+    flash: None,
+    firmware: &[],
+    telemetry: None,
+    power_states: None,
+};
+
+/// A Tenstorrent chip architecture.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    /// The Grayskull architecture.
+    Grayskull,
+    /// The Wormhole architecture.
+    Wormhole,
+    /// The Blackhole architecture.
+    Blackhole,
+    /// An unknown architecture (bound through `new_id`).
+    Unknown,
+}
+
+/// A board description, matched by the PCI subsystem device ID.
+///
+/// The subsystem device ID carries the board type code (e.g., `0x18` for the
+/// Wormhole n300).
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct Board {
+    /// The PCI subsystem device ID.
+    pub subsystem_device_id: u16,
+    /// The board name.
+    pub name: &'static CStr,
+    /// The bounds of the power and clock limits.
+    pub power: PowerBounds,
+}
+
 /// A hardware configuration description.
 #[allow(unreachable_pub, reason = "rust-for-linux")]
+#[derive(Clone, Copy)]
 pub struct HwConfig {
     /// The PCI vendor ID.
     pub vendor_id: u32,
@@ -27,6 +156,21 @@ pub struct HwConfig {
     pub device_id: u32,
     /// The hardware device name.
     pub name: &'static CStr,
+    /// The chip architecture.
+    pub arch: Arch,
+    /// The known boards carrying this chip.
+    pub boards: &'static [Board],
+    /// The grid of cores.
+    pub grid: &'static Grid,
+    /// The layout of the SPI flash of the boards, if known.
+    pub flash: Option<&'static FlashLayout>,
+    /// The firmware images loaded by the driver.
+    pub firmware: &'static [FwImage],
+    /// The layout of the telemetry table of the ARC firmware, if known.
+    pub telemetry: Option<&'static TelemetryLayout>,
+    /// The messages moving the ARC firmware between its power states, if it
+    /// can be messaged.
+    pub power_states: Option<&'static PowerStates>,
 }
 
 impl HwConfig {
@@ -41,4 +185,28 @@ impl HwConfig {
     pub(crate) const fn pci_device_table_item(self) -> (::kernel::pci::DeviceId, Self) {
         (self.device_id(), self)
     }
+
+    /// Returns the board matching the PCI subsystem device ID, if known.
+    pub(crate) fn board(&self, subsystem_device_id: u16) -> Option<&'static Board> {
+        self.boards
+            .iter()
+            .find(|board| board.subsystem_device_id == subsystem_device_id)
+    }
+
+    /// Returns `true` if the ARC firmware of chips with this configuration can
+    /// be messaged (through the scratch registers, see
+    /// [`arc::send_scratch`]).
+    ///
+    /// NOTE: Blackhole firmware is messaged through a queue in ARC memory,
+    /// which is not supported yet. Its power management, power limits and
+    /// power states are thus not supported either.
+    pub(crate) const fn has_arc_msg(&self) -> bool {
+        matches!(self.arch, Arch::Grayskull | Arch::Wormhole)
+    }
+
+    /// Returns `true` if devices with this configuration are bound in
+    /// identify-only mode (without any BAR access features).
+    pub(crate) const fn is_identify_only(&self) -> bool {
+        matches!(self.arch, Arch::Unknown)
+    }
 }
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::kernel::{
+    prelude::*,
+    time::{Delta, delay::fsleep},
+};
+
+use crate::tt::hw::RegIo;
+
+/// The BAR0 offset of the reset unit registers.
+pub(crate) const RESET_UNIT: usize = 0x1FF3_0000;
+
+/// The BAR0 offset of the `ARC_MISC_CNTL` register.
+pub(super) const ARC_MISC_CNTL: usize = RESET_UNIT + 0x0100;
+
+/// The `ARC_MISC_CNTL` bit which raises IRQ 0 on the ARC.
+pub(super) const ARC_MISC_CNTL_IRQ0: u32 = 1 << 16;
+
+/// The BAR0 offset of the scratch register holding the message (`SCRATCH[5]`).
+pub(super) const SCRATCH_MSG: usize = scratch(5);
+
+/// The BAR0 offset of the scratch register holding the argument and exit code
+/// (`SCRATCH[3]`).
+pub(super) const SCRATCH_ARG: usize = scratch(3);
+
+/// The number of scratch registers of the reset unit.
+pub(crate) const SCRATCH_COUNT: usize = 8;
+
+/// The BAR0 offset of the window onto the ARC CSM (closely-coupled memory).
+pub(crate) const CSM_BAR0: usize = 0x1FE0_0000;
+
+/// The ARC address of the start of the CSM.
+pub(crate) const CSM_BASE: u32 = 0x1000_0000;
+
+/// The size of the CSM (and of its window).
+pub(crate) const CSM_SIZE: u32 = 0x0008_0000;
+
+/// The marker combined with a message code to mark it as a command.
+pub(super) const MSG_COMMAND: u32 = 0xAA00;
+
+/// The interval between polls for the acknowledgement of a message.
+const POLL_INTERVAL: Delta = Delta::from_micros(100);
+
+/// A message to the ARC management firmware.
+#[derive(Clone, Copy)]
+pub(crate) enum ArcMsg {
+    /// Enter the highest performance power state (A-state 0).
+    AState0,
+    /// Enter the lowest power state (A-state 3).
+    AState3,
+    /// Prepare for the host going to sleep or powering down.
+    GoToSleep,
+    /// Report the harvesting mask (returned in place of the exit code).
+    GetHarvesting,
+    /// Report the ARC address of the buffer for SPI flash transfers (returned
+    /// in place of the exit code).
+    GetSpiBuffer,
+    /// Read the flash block at the given address into the SPI buffer.
+    SpiRead(u32),
+    /// Write the SPI buffer to the flash block at the given address (erasing
+    /// it first).
+    SpiWrite(u32),
+    /// Report the ARC address of the telemetry table (returned in place of the
+    /// exit code).
+    GetTelemetry,
+    /// Limit the AI clock to `min ..= max` MHz (given as `(min, max)`).
+    SetAiclkRange(u32, u32),
+    /// Limit the power of the chip to the given watts.
+    SetPowerLimit(u32),
+    /// Throttle the chip from the given temperature in degrees Celsius.
+    SetThermalLimit(u32),
+}
+
+impl ArcMsg {
+    /// Returns the message code.
+    pub(crate) const fn code(self) -> u8 {
+        match self {
+            Self::AState0 => 0xA0,
+            Self::AState3 => 0xA3,
+            Self::GoToSleep => 0x55,
+            Self::GetHarvesting => 0x57,
+            Self::GetSpiBuffer => 0x29,
+            Self::SpiRead(_) => 0x2A,
+            Self::SpiWrite(_) => 0x2B,
+            Self::GetTelemetry => 0x2C,
+            Self::SetAiclkRange(..) => 0x2D,
+            Self::SetPowerLimit(_) => 0x2E,
+            Self::SetThermalLimit(_) => 0x2F,
+        }
+    }
+
+    /// Returns the argument of the message.
+    ///
+    /// The AI clock range is packed as `max << 16 | min`.
+    pub(crate) const fn arg(self) -> u32 {
+        match self {
+            Self::SpiRead(addr) | Self::SpiWrite(addr) => addr,
+            Self::SetAiclkRange(min, max) => (max << 16) | (min & 0xFFFF),
+            Self::SetPowerLimit(value) | Self::SetThermalLimit(value) => value,
+            Self::AState0
+            | Self::AState3
+            | Self::GoToSleep
+            | Self::GetHarvesting
+            | Self::GetSpiBuffer
+            | Self::GetTelemetry => 0,
+        }
+    }
+
+    /// Returns the time to wait for the acknowledgement of the message.
+    pub(crate) const fn timeout(self) -> Delta {
+        match self {
+            Self::AState0
+            | Self::AState3
+            | Self::GetHarvesting
+            | Self::GetSpiBuffer
+            | Self::SpiRead(_)
+            | Self::GetTelemetry
+            | Self::SetAiclkRange(..)
+            | Self::SetPowerLimit(_)
+            | Self::SetThermalLimit(_) => Delta::from_millis(100),
+            Self::GoToSleep => Delta::from_millis(1000),
+            Self::SpiWrite(_) => Delta::from_millis(2000),
+        }
+    }
+}
+
+/// Returns the BAR0 offset of the scratch register `index` of the reset unit.
+pub(crate) const fn scratch(index: usize) -> usize {
+    RESET_UNIT + 0x0060 + 4 * index
+}
+
+/// Returns the BAR0 offset of the `len` bytes at the ARC address `addr` within
+/// the CSM.
+///
+/// # Errors
+///
+/// * Errors with `EIO` if the range is misaligned or not within the CSM.
+pub(crate) fn csm_offset(addr: u32, len: u32) -> Result<usize> {
+    let rel = addr.checked_sub(CSM_BASE).ok_or(EIO)?;
+    let end = rel.checked_add(len).ok_or(EIO)?;
+    if rel % 4 != 0 || end > CSM_SIZE {
+        return Err(EIO);
+    }
+    Ok(CSM_BAR0 + usize::try_from(rel)?)
+}
+
+/// Sends `msg` through the scratch registers of the reset unit (as used by
+/// Grayskull and Wormhole firmware) and waits (bounded by [`ArcMsg::timeout`])
+/// for the acknowledgement.
+///
+/// Returns the reply to the message: its exit code, or the value some messages
+/// return in its place.
+///
+/// # Errors
+///
+/// * Errors with `EBUSY` if the ARC has not yet serviced a previous message.
+/// * Errors with `ETIMEDOUT` if the message is not acknowledged in time.
+/// * Errors if accessing the registers fails.
+pub(crate) fn send_scratch<R: RegIo + ?Sized>(regs: &R, msg: ArcMsg) -> Result<u32> {
+    let code = u32::from(msg.code());
+
+    let cntl = regs.read_reg(ARC_MISC_CNTL)?;
+    if cntl & ARC_MISC_CNTL_IRQ0 != 0 {
+        return Err(EBUSY);
+    }
+
+    regs.write_reg(SCRATCH_ARG, msg.arg())?;
+    regs.write_reg(SCRATCH_MSG, MSG_COMMAND | code)?;
+    regs.write_reg(ARC_MISC_CNTL, cntl | ARC_MISC_CNTL_IRQ0)?;
+
+    // The firmware replaces the command with the bare message code when done.
+    let polls = msg.timeout().as_micros_ceil() / POLL_INTERVAL.as_micros_ceil();
+    for _poll in 0 ..= polls {
+        if regs.read_reg(SCRATCH_MSG)? == code {
+            return regs.read_reg(SCRATCH_ARG);
+        }
+        fsleep(POLL_INTERVAL);
+    }
+
+    Err(ETIMEDOUT)
+}
@@ -2,11 +2,200 @@
 
 use ::kernel::c_str;
 
-use crate::tt::hw::HwConfig;
+use crate::tt::hw::{
+    Arch,
+    Board,
+    HwConfig,
+    flash::FlashLayout,
+    grid::{CoreKind, Grid, Translation},
+    power::PowerBounds,
+};
 
 /// The Tenstorrent Blackhole hardware configuration description.
 pub(crate) const HWCONFIG_BLACKHOLE: HwConfig = HwConfig {
     vendor_id: crate::tt::hw::PCI_VENDOR_ID_TENSTORRENT,
     device_id: 0xB140,
     name: c_str!("Blackhole"),
+    arch: Arch::Blackhole,
+    boards: &BOARDS,
+    grid: &GRID,
+    flash: Some(&FLASH),
+    // NOTE: The ARC firmware (CMFW) is booted from the SPI flash, and the
+    // Ethernet cores are only reachable through the NOC, which is not
+    // supported yet. No images are loaded.
+    firmware: &[],
+    telemetry: None,
+    power_states: None,
+};
+
+/// The known Blackhole boards.
+const BOARDS: [Board; 9] = [
+    Board {
+        subsystem_device_id: 0x0036,
+        name: c_str!("p100"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 300),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0040,
+        name: c_str!("p150a"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 300),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0041,
+        name: c_str!("p150b"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 300),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0042,
+        name: c_str!("p150c"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 300),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0043,
+        name: c_str!("p100a"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 300),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0044,
+        name: c_str!("p300b"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 200),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0045,
+        name: c_str!("p300a"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 200),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0046,
+        name: c_str!("p300c"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 200),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0047,
+        name: c_str!("Galaxy"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (100, 200),
+            thermal: THERMAL,
+        },
+    },
+];
+
+/// The bounds of the AI clock of Blackhole chips in MHz.
+///
+/// The bounds of the boards follow the public board specifications (the power
+/// limits are per chip, so half the board power of the p300).
+const AICLK: (u32, u32) = (800, 1350);
+
+/// The bounds of the thermal limit of Blackhole chips in degrees Celsius.
+const THERMAL: (u32, u32) = (50, 90);
+
+/// The Blackhole grid.
+///
+/// Harvested chips have Tensix columns, DRAM banks and Ethernet cores
+/// disabled, as reported through telemetry (not supported yet).
+///
+/// DRAM, PCIe and Ethernet cores are translated to columns beyond the Tensix
+/// grid. Tensix cores keep their coordinates.
+///
+/// NOTE: On harvested chips, the firmware also translates the disabled Tensix
+/// columns to the end of the grid, which is not modeled since harvesting is not
+/// reported yet.
+const GRID: Grid = Grid {
+    width: 17,
+    height: 12,
+    tensix_cols: &[1, 2, 3, 4, 5, 6, 7, 10, 11, 12, 13, 14, 15, 16],
+    tensix_rows: &[2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    dram: &[
+        &[(0, 0), (0, 1), (0, 11)],
+        &[(0, 2), (0, 10), (0, 3)],
+        &[(0, 9), (0, 4), (0, 8)],
+        &[(0, 5), (0, 7), (0, 6)],
+        &[(9, 0), (9, 1), (9, 11)],
+        &[(9, 2), (9, 10), (9, 3)],
+        &[(9, 9), (9, 4), (9, 8)],
+        &[(9, 5), (9, 7), (9, 6)],
+    ],
+    eth: &[
+        (1, 1),
+        (16, 1),
+        (2, 1),
+        (15, 1),
+        (3, 1),
+        (14, 1),
+        (4, 1),
+        (13, 1),
+        (5, 1),
+        (12, 1),
+        (6, 1),
+        (11, 1),
+        (7, 1),
+        (10, 1),
+    ],
+    pcie: &[(2, 0), (11, 0)],
+    arc: &[(8, 0)],
+    harvest_rows: &[],
+    translation: Some(&[
+        Translation {
+            kind: CoreKind::Dram,
+            origin: (17, 12),
+            rows: 12,
+        },
+        Translation {
+            kind: CoreKind::Pcie,
+            origin: (19, 24),
+            rows: 1,
+        },
+        Translation {
+            kind: CoreKind::Eth,
+            origin: (20, 25),
+            rows: 1,
+        },
+    ]),
+};
+
+/// The layout of the SPI flash of Blackhole boards.
+///
+/// The last 64 KiB hold the board identity and calibration.
+///
+/// NOTE: Blackhole firmware transfers flash blocks through its message queue
+/// in ARC memory, which is only reachable through the NOC and not supported
+/// yet, so there is no buffer window.
+const FLASH: FlashLayout = FlashLayout {
+    size: 0x0400_0000,
+    block_size: 0x1000,
+    writable: (0, 0x03FF_0000),
+    buffer: None,
 };
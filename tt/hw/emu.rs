@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::kernel::{
+    prelude::*,
+    sync::{Arc, Mutex},
+};
+
+use crate::tt::hw::{
+    BAR0_SIZE,
+    RegIo,
+    arc::{ARC_MISC_CNTL, ARC_MISC_CNTL_IRQ0, ArcMsg, CSM_BASE, MSG_COMMAND, RESET_UNIT, SCRATCH_ARG, SCRATCH_MSG},
+};
+
+/// The size of the modeled register window (the reset unit).
+const WINDOW_SIZE: usize = 0x1000;
+
+/// The register model of an emulated (Wormhole) chip.
+///
+/// Only the reset unit is backed by storage. Other registers within BAR0 read
+/// as zero and ignore writes. The model stands in for the ARC firmware: raising
+/// IRQ 0 through `ARC_MISC_CNTL` immediately acknowledges a pending command
+/// in the scratch registers with an exit code of 0, as the firmware would. The
+/// SPI buffer and the telemetry table are reported at the start of the CSM, so
+/// the flash and the telemetry read as zero, and the flash ignores writes. The
+/// TLB registers are not modeled, so windows programmed through them read as
+/// zero.
+#[pin_data]
+pub(crate) struct EmuRegs {
+    /// The registers of the reset unit (by word).
+    #[pin]
+    window: Mutex<KVec<u32>>,
+}
+
+impl EmuRegs {
+    /// Creates a register model in its power-on state.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the model fails.
+    pub(crate) fn new() -> Result<Arc<Self>> {
+        let window = KVec::from_elem(0u32, WINDOW_SIZE / 4, GFP_KERNEL)?;
+        let init = pin_init!(Self {
+            window <- ::kernel::new_mutex!(window),
+        });
+        Arc::pin_init(init, GFP_KERNEL)
+    }
+
+    /// Returns the index within the window of the register at `offset` (if it
+    /// is modeled).
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EINVAL` if `offset` is out of bounds or misaligned.
+    fn index(offset: usize) -> Result<Option<usize>> {
+        if offset % 4 != 0 || offset > BAR0_SIZE - 4 {
+            return Err(EINVAL);
+        }
+        let index = offset
+            .checked_sub(RESET_UNIT)
+            .filter(|&rel| rel < WINDOW_SIZE)
+            .map(|rel| rel / 4);
+        Ok(index)
+    }
+
+    /// Services a message raised through `ARC_MISC_CNTL` as the firmware would.
+    fn serve_arc(window: &mut [u32]) {
+        let msg = SCRATCH_MSG - RESET_UNIT;
+        let arg = SCRATCH_ARG - RESET_UNIT;
+        let cntl = ARC_MISC_CNTL - RESET_UNIT;
+        let command = window[msg / 4];
+        if command & 0xFF00 == MSG_COMMAND {
+            let code = command & 0xFF;
+            window[msg / 4] = code;
+            let addressed = [ArcMsg::GetSpiBuffer, ArcMsg::GetTelemetry];
+            window[arg / 4] = if addressed.iter().any(|msg| code == u32::from(msg.code())) {
+                CSM_BASE
+            } else {
+                0
+            };
+        }
+        window[cntl / 4] &= !ARC_MISC_CNTL_IRQ0;
+    }
+}
+
+impl RegIo for EmuRegs {
+    fn read_reg(&self, offset: usize) -> Result<u32> {
+        let Some(index) = Self::index(offset)? else {
+            return Ok(0);
+        };
+        Ok(self.window.lock()[index])
+    }
+
+    fn write_reg(&self, offset: usize, value: u32) -> Result {
+        let Some(index) = Self::index(offset)? else {
+            return Ok(());
+        };
+        let mut window = self.window.lock();
+        window[index] = value;
+        if offset == ARC_MISC_CNTL && value & ARC_MISC_CNTL_IRQ0 != 0 {
+            Self::serve_arc(&mut window);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: The flash is accessed one block at a time through a buffer in ARC
+// memory: the firmware reads a block into the buffer, or writes the buffer to a
+// block (see `ArcMsg::SpiRead` and `ArcMsg::SpiWrite`), and the host copies the
+// buffer through a window of BAR0.
+//
+// The message codes of the transfers are not documented by a published
+// firmware interface, so blocks are only transferred with the
+// `unsafe_flash_access` parameter set (failing with `EOPNOTSUPP` otherwise).
+
+use ::kernel::prelude::*;
+
+use crate::tt::{
+    hw::RegIo,
+    uapi::{FLASH_BUNDLE_MAGIC, FLASH_BUNDLE_VERSION, FLASH_SIGNATURE_MAX, FlashBundle, FlashInfo},
+};
+
+/// The layout of the SPI flash of the boards of an architecture.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct FlashLayout {
+    /// The size of the flash.
+    pub size: u32,
+    /// The size of a block: the unit in which the firmware reads and writes the
+    /// flash.
+    pub block_size: u32,
+    /// The region which may be erased and written, as `(start, end)`. The rest
+    /// holds the board identity and calibration, and is read-only.
+    pub writable: (u32, u32),
+    /// The window onto the buffer of the firmware, if the flash can be accessed
+    /// through it.
+    pub buffer: Option<BufferWindow>,
+}
+
+/// A window of BAR0 onto the ARC memory holding the buffer for flash transfers.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct BufferWindow {
+    /// The BAR0 offset of the window.
+    pub bar0: usize,
+    /// The ARC address at the start of the window.
+    pub arc: u32,
+    /// The size of the window.
+    pub size: u32,
+}
+
+impl FlashLayout {
+    /// Returns the layout as reported to userspace.
+    pub(crate) fn info(&self, write_protected: bool) -> FlashInfo {
+        FlashInfo {
+            size: self.size,
+            block_size: self.block_size,
+            writable_start: self.writable.0,
+            writable_end: self.writable.1,
+            write_protected: u32::from(write_protected),
+            reserved: 0,
+        }
+    }
+
+    /// Checks that the range of `length` bytes at `offset` is block-aligned and
+    /// within the flash.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EINVAL` if the range is misaligned or out of bounds.
+    pub(crate) fn check_range(&self, offset: u32, length: u32) -> Result {
+        let end = offset.checked_add(length).ok_or(EINVAL)?;
+        if offset % self.block_size != 0 || length % self.block_size != 0 || end > self.size {
+            return Err(EINVAL);
+        }
+        Ok(())
+    }
+
+    /// Checks that the range of `length` bytes at `offset` may be erased and
+    /// written.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EINVAL` if the range is misaligned or out of bounds.
+    /// * Errors with `EACCES` if the range is not within the writable region.
+    pub(crate) fn check_writable(&self, offset: u32, length: u32) -> Result {
+        self.check_range(offset, length)?;
+        let (start, end) = self.writable;
+        if offset < start || offset + length > end {
+            return Err(EACCES);
+        }
+        Ok(())
+    }
+
+    /// Checks the header of a bundle of `length` bytes for chips with PCI device
+    /// ID `device_id`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EINVAL` if the header is malformed, its lengths do not
+    ///   add up to `length`, or the image is not built for `device_id`.
+    /// * Errors with `EACCES` if the image is not within the writable region.
+    pub(crate) fn check_bundle(&self, device_id: u32, bundle: &FlashBundle, length: usize) -> Result {
+        if bundle.magic != FLASH_BUNDLE_MAGIC || bundle.version != FLASH_BUNDLE_VERSION {
+            return Err(EINVAL);
+        }
+        if bundle.device_id != device_id {
+            return Err(EINVAL);
+        }
+        if bundle.signature_length == 0 || bundle.signature_length > FLASH_SIGNATURE_MAX {
+            return Err(EINVAL);
+        }
+        let expected = usize::try_from(bundle.signature_length)?
+            .checked_add(usize::try_from(bundle.length)?)
+            .and_then(|len| len.checked_add(size_of::<FlashBundle>()))
+            .ok_or(EINVAL)?;
+        if expected != length {
+            return Err(EINVAL);
+        }
+        self.check_writable(bundle.offset, bundle.length)
+    }
+
+    /// Returns the BAR0 offset of the block-sized buffer at the ARC address
+    /// `buffer` (as reported by the firmware).
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if the flash cannot be accessed.
+    /// * Errors with `EIO` if the buffer is not within the window.
+    pub(crate) fn buffer_offset(&self, buffer: u32) -> Result<usize> {
+        let window = self.buffer.as_ref().ok_or(EOPNOTSUPP)?;
+        let rel = buffer.checked_sub(window.arc).ok_or(EIO)?;
+        let end = rel.checked_add(self.block_size).ok_or(EIO)?;
+        if rel % 4 != 0 || end > window.size {
+            return Err(EIO);
+        }
+        Ok(window.bar0 + usize::try_from(rel)?)
+    }
+}
+
+/// The size of the header of a bundle.
+pub(crate) const BUNDLE_HEADER_SIZE: usize = size_of::<FlashBundle>();
+
+/// Returns the header of `bundle` as covered by its signature: the fields in
+/// memory order, with `signature_length` zero (as it is not known when the
+/// bundle is signed).
+pub(crate) fn signed_header(bundle: &FlashBundle) -> [u8; BUNDLE_HEADER_SIZE] {
+    let fields = [
+        bundle.magic,
+        bundle.version,
+        bundle.device_id,
+        bundle.offset,
+        bundle.length,
+        0,
+    ];
+    let mut header = [0u8; BUNDLE_HEADER_SIZE];
+    for (bytes, field) in header.chunks_exact_mut(4).zip(fields) {
+        bytes.copy_from_slice(&field.to_ne_bytes());
+    }
+    header
+}
+
+/// Copies the buffer at the BAR0 `offset` into `block`.
+///
+/// # Errors
+///
+/// * Errors if reading the buffer fails.
+pub(crate) fn read_buffer<R: RegIo + ?Sized>(regs: &R, offset: usize, block: &mut [u8]) -> Result {
+    for (word, chunk) in (offset ..).step_by(4).zip(block.chunks_exact_mut(4)) {
+        chunk.copy_from_slice(&regs.read_reg(word)?.to_le_bytes());
+    }
+    Ok(())
+}
+
+/// Copies `block` into the buffer at the BAR0 `offset`.
+///
+/// # Errors
+///
+/// * Errors if writing the buffer fails.
+pub(crate) fn write_buffer<R: RegIo + ?Sized>(regs: &R, offset: usize, block: &[u8]) -> Result {
+    for (word, chunk) in (offset ..).step_by(4).zip(block.chunks_exact(4)) {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(chunk);
+        regs.write_reg(word, u32::from_le_bytes(bytes))?;
+    }
+    Ok(())
+}
+
+#[cfg(CONFIG_KUNIT)]
+#[::kernel::macros::kunit_tests(tt_core_flash)]
+mod tests {
+    use ::kernel::prelude::*;
+
+    use super::{FlashLayout, signed_header};
+    use crate::tt::{
+        hw::{HWCONFIG_BLACKHOLE, HWCONFIG_GRAYSKULL, HWCONFIG_WORMHOLE},
+        uapi::{FLASH_BUNDLE_MAGIC, FLASH_BUNDLE_VERSION, FlashBundle},
+    };
+
+    /// Returns the flash layout of Wormhole.
+    fn wormhole() -> Result<&'static FlashLayout> {
+        HWCONFIG_WORMHOLE.flash.ok_or(ENODEV)
+    }
+
+    /// Returns a valid bundle header for a Wormhole image of one block at
+    /// `offset`.
+    const fn bundle(offset: u32) -> FlashBundle {
+        FlashBundle {
+            magic: FLASH_BUNDLE_MAGIC,
+            version: FLASH_BUNDLE_VERSION,
+            device_id: HWCONFIG_WORMHOLE.device_id,
+            offset,
+            length: 0x1000,
+            signature_length: 0x100,
+        }
+    }
+
+    #[test]
+    fn layouts_are_consistent() {
+        for hw in [HWCONFIG_GRAYSKULL, HWCONFIG_WORMHOLE, HWCONFIG_BLACKHOLE] {
+            let Some(layout) = hw.flash else {
+                continue;
+            };
+            let (start, end) = layout.writable;
+            assert!(layout.block_size.is_power_of_two());
+            assert!(layout.check_range(start, end - start).is_ok());
+            assert!(layout.check_range(0, layout.size).is_ok());
+        }
+    }
+
+    #[test]
+    fn ranges() -> Result {
+        let layout = wormhole()?;
+        assert!(layout.check_range(0, 0x1000).is_ok());
+        assert_eq!(layout.check_range(1, 0x1000).err(), Some(EINVAL));
+        assert_eq!(layout.check_range(0, 0x800).err(), Some(EINVAL));
+        assert_eq!(layout.check_range(layout.size, 0x1000).err(), Some(EINVAL));
+        assert_eq!(layout.check_range(0xFFFF_F000, 0x2000).err(), Some(EINVAL));
+        assert!(layout.check_writable(0, 0x1000).is_ok());
+        assert_eq!(layout.check_writable(layout.writable.1, 0x1000).err(), Some(EACCES));
+        Ok(())
+    }
+
+    #[test]
+    fn bundles() -> Result {
+        let layout = wormhole()?;
+        let device_id = HWCONFIG_WORMHOLE.device_id;
+        let length = size_of::<FlashBundle>() + 0x100 + 0x1000;
+        assert!(layout.check_bundle(device_id, &bundle(0), length).is_ok());
+        assert_eq!(
+            layout.check_bundle(device_id, &bundle(0), length - 1).err(),
+            Some(EINVAL)
+        );
+        assert_eq!(
+            layout
+                .check_bundle(HWCONFIG_BLACKHOLE.device_id, &bundle(0), length)
+                .err(),
+            Some(EINVAL)
+        );
+        assert_eq!(
+            layout.check_bundle(device_id, &bundle(layout.writable.1), length).err(),
+            Some(EACCES)
+        );
+
+        let unsigned = FlashBundle {
+            signature_length: 0,
+            ..bundle(0)
+        };
+        assert_eq!(
+            layout.check_bundle(device_id, &unsigned, length - 0x100).err(),
+            Some(EINVAL)
+        );
+        let foreign = FlashBundle { magic: 0, ..bundle(0) };
+        assert_eq!(layout.check_bundle(device_id, &foreign, length).err(), Some(EINVAL));
+        Ok(())
+    }
+
+    #[test]
+    fn buffer_offsets() -> Result {
+        let layout = wormhole()?;
+        assert_eq!(layout.buffer_offset(0x1000_0000), Ok(0x1FE0_0000));
+        assert_eq!(layout.buffer_offset(0x1000_0100), Ok(0x1FE0_0100));
+        assert_eq!(layout.buffer_offset(0x0FFF_FFFC).err(), Some(EIO));
+        assert_eq!(layout.buffer_offset(0x1000_0002).err(), Some(EIO));
+        assert_eq!(layout.buffer_offset(0x1008_0000 - 0x800).err(), Some(EIO));
+        let blackhole = HWCONFIG_BLACKHOLE.flash.ok_or(ENODEV)?;
+        assert_eq!(blackhole.buffer_offset(0x1000_0000).err(), Some(EOPNOTSUPP));
+        Ok(())
+    }
+
+    #[test]
+    fn signed_headers() {
+        let header = signed_header(&bundle(0x1000));
+        assert_eq!(header.len(), size_of::<FlashBundle>());
+        assert_eq!(&header[.. 4], b"TTFB");
+        assert_eq!(&header[12 .. 16], &0x1000u32.to_ne_bytes());
+        assert_eq!(&header[20 ..], &[0; 4]);
+    }
+}
@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: Images are loaded with the kernel firmware loader from
+// `tenstorrent/<arch>/<name>.bin`. Each file starts with a header of eight
+// little-endian words (see `Header`), followed by the image.
+
+use ::kernel::prelude::*;
+
+/// The magic of the header of a firmware file (`"TTFW"` in memory).
+const MAGIC: u32 = u32::from_le_bytes(*b"TTFW");
+
+/// The version of the header layout.
+const HEADER_VERSION: u32 = 1;
+
+/// The size of the header.
+const HEADER_SIZE: usize = 32;
+
+/// The maximum number of firmware images of an architecture.
+pub(crate) const MAX_IMAGES: usize = 4;
+
+/// The memory a firmware image is written to.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FwTarget {
+    /// The L1 of every Ethernet core, whose RISC-V core is held in reset while
+    /// the image is written.
+    EthL1,
+}
+
+/// A firmware image loaded by the driver.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct FwImage {
+    /// The name of the image (as shown in sysfs).
+    pub name: &'static CStr,
+    /// The path of the file (relative to the firmware search path).
+    pub path: &'static CStr,
+    /// The oldest version of the image supported by the driver.
+    pub min_version: u32,
+    /// The memory the image is written to.
+    pub target: FwTarget,
+    /// The size of the target memory (from address 0).
+    pub size: u32,
+}
+
+/// The header of a firmware file.
+pub(crate) struct Header {
+    /// The version of the image, packed (from the high byte) as major, minor,
+    /// patch and build.
+    pub(crate) version: u32,
+    /// The address the image is written to (within the target memory).
+    pub(crate) load_addr: u32,
+}
+
+impl FwImage {
+    /// Checks the firmware file `data` for chips with PCI device ID `device_id`
+    /// and returns its header and image.
+    ///
+    /// The header holds, in order: the magic, the version of the header
+    /// layout, the PCI device ID, the version of the image, its load address,
+    /// its length, the wrapping sum of its words, and a reserved word.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EINVAL` if the header is malformed or the image is not
+    ///   built for `device_id`.
+    /// * Errors with `EINVAL` if the image does not fit the target memory or
+    ///   its checksum does not match.
+    /// * Errors with `ERANGE` if the version is older than
+    ///   [`FwImage::min_version`].
+    pub(crate) fn check<'a>(&self, device_id: u32, data: &'a [u8]) -> Result<(Header, &'a [u8])> {
+        let header = data.get(.. HEADER_SIZE).ok_or(EINVAL)?;
+        let image = data.get(HEADER_SIZE ..).ok_or(EINVAL)?;
+        let mut words = [0u32; HEADER_SIZE / 4];
+        for (word, chunk) in words.iter_mut().zip(header.chunks_exact(4)) {
+            *word = le_word(chunk);
+        }
+        let [
+            magic,
+            header_version,
+            id,
+            version,
+            load_addr,
+            length,
+            checksum,
+            _reserved,
+        ] = words;
+
+        if magic != MAGIC || header_version != HEADER_VERSION || id != device_id {
+            return Err(EINVAL);
+        }
+        let end = load_addr.checked_add(length).ok_or(EINVAL)?;
+        if usize::try_from(length)? != image.len() || length % 4 != 0 || load_addr % 4 != 0 || end > self.size {
+            return Err(EINVAL);
+        }
+        if sum(image) != checksum {
+            return Err(EINVAL);
+        }
+        if version < self.min_version {
+            return Err(ERANGE);
+        }
+        Ok((Header { version, load_addr }, image))
+    }
+}
+
+/// Returns the little-endian word `chunk` (of four bytes).
+fn le_word(chunk: &[u8]) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(chunk);
+    u32::from_le_bytes(bytes)
+}
+
+/// Returns the wrapping sum of the little-endian words of `image`.
+fn sum(image: &[u8]) -> u32 {
+    image
+        .chunks_exact(4)
+        .fold(0u32, |acc, chunk| acc.wrapping_add(le_word(chunk)))
+}
+
+#[cfg(CONFIG_KUNIT)]
+#[::kernel::macros::kunit_tests(tt_core_fw)]
+mod tests {
+    use ::kernel::prelude::*;
+
+    use super::{FwImage, FwTarget, HEADER_VERSION, MAGIC, sum};
+    use crate::tt::hw::{HWCONFIG_BLACKHOLE, HWCONFIG_GRAYSKULL, HWCONFIG_WORMHOLE};
+
+    /// The PCI device ID of the test files.
+    const DEVICE_ID: u32 = 0x401E;
+
+    /// An image with a 64 KiB target supporting version 1.2.0.0 and later.
+    const IMAGE: FwImage = FwImage {
+        name: ::kernel::c_str!("test"),
+        path: ::kernel::c_str!("tenstorrent/test.bin"),
+        min_version: 0x0102_0000,
+        target: FwTarget::EthL1,
+        size: 0x1_0000,
+    };
+
+    /// Returns a firmware file of `image` with the header `words` (the
+    /// checksum is computed unless given).
+    fn file(words: [u32; 8], image: &[u8]) -> Result<KVec<u8>> {
+        let mut data = KVec::new();
+        for word in words {
+            data.extend_from_slice(&word.to_le_bytes(), GFP_KERNEL)?;
+        }
+        data.extend_from_slice(image, GFP_KERNEL)?;
+        Ok(data)
+    }
+
+    /// Returns a valid header for `image` at `load_addr` with `version`.
+    fn header(image: &[u8], load_addr: u32, version: u32) -> Result<[u32; 8]> {
+        let length = u32::try_from(image.len())?;
+        Ok([
+            MAGIC,
+            HEADER_VERSION,
+            DEVICE_ID,
+            version,
+            load_addr,
+            length,
+            sum(image),
+            0,
+        ])
+    }
+
+    #[test]
+    fn tables_are_bounded() {
+        for hw in [HWCONFIG_GRAYSKULL, HWCONFIG_WORMHOLE, HWCONFIG_BLACKHOLE] {
+            assert!(hw.firmware.len() <= super::MAX_IMAGES);
+        }
+    }
+
+    #[test]
+    fn valid_files() -> Result {
+        let image = [1, 0, 0, 0, 2, 0, 0, 0];
+        let data = file(header(&image, 0x100, 0x0102_0304)?, &image)?;
+        let (header, loaded) = IMAGE.check(DEVICE_ID, &data)?;
+        assert_eq!(header.version, 0x0102_0304);
+        assert_eq!(header.load_addr, 0x100);
+        assert_eq!(loaded, &image);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_files() -> Result {
+        let image = [1, 0, 0, 0, 2, 0, 0, 0];
+        let valid = header(&image, 0, 0x0102_0000)?;
+
+        assert_eq!(IMAGE.check(DEVICE_ID, &image).err(), Some(EINVAL));
+        assert_eq!(IMAGE.check(0xB140, &file(valid, &image)?).err(), Some(EINVAL));
+        assert_eq!(IMAGE.check(DEVICE_ID, &file(valid, &image[.. 4])?).err(), Some(EINVAL));
+
+        let [magic, version, id, image_version, load_addr, length, checksum, reserved] = valid;
+        let bad_magic = [
+            !magic,
+            version,
+            id,
+            image_version,
+            load_addr,
+            length,
+            checksum,
+            reserved,
+        ];
+        assert_eq!(IMAGE.check(DEVICE_ID, &file(bad_magic, &image)?).err(), Some(EINVAL));
+        let outside = [magic, version, id, image_version, 0xFFFC, length, checksum, reserved];
+        assert_eq!(IMAGE.check(DEVICE_ID, &file(outside, &image)?).err(), Some(EINVAL));
+        let bad_sum = [
+            magic,
+            version,
+            id,
+            image_version,
+            load_addr,
+            length,
+            checksum + 1,
+            reserved,
+        ];
+        assert_eq!(IMAGE.check(DEVICE_ID, &file(bad_sum, &image)?).err(), Some(EINVAL));
+        let too_old = [magic, version, id, 0x0101_FFFF, load_addr, length, checksum, reserved];
+        assert_eq!(IMAGE.check(DEVICE_ID, &file(too_old, &image)?).err(), Some(ERANGE));
+        Ok(())
+    }
+}
@@ -2,11 +2,76 @@
 
 use ::kernel::c_str;
 
-use crate::tt::hw::HwConfig;
+use crate::tt::hw::{
+    Arch,
+    HwConfig,
+    arc::{self, ArcMsg},
+    flash::{BufferWindow, FlashLayout},
+    grid::Grid,
+    power::PowerStates,
+};
 
 /// The Tenstorrent Grayskull hardware configuration description.
 pub(crate) const HWCONFIG_GRAYSKULL: HwConfig = HwConfig {
     vendor_id: crate::tt::hw::PCI_VENDOR_ID_TENSTORRENT,
     device_id: 0xFACA,
     name: c_str!("Grayskull"),
+    arch: Arch::Grayskull,
+    boards: &[],
+    grid: &GRID,
+    flash: Some(&FLASH),
+    firmware: &[],
+    telemetry: None,
+    power_states: Some(&POWER_STATES),
+};
+
+/// The Grayskull grid.
+///
+/// Harvested chips have Tensix rows disabled. The firmware reports them in the
+/// order of [`Grid::harvest_rows`]. Coordinates are not translated.
+const GRID: Grid = Grid {
+    width: 13,
+    height: 12,
+    tensix_cols: &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+    tensix_rows: &[1, 2, 3, 4, 5, 7, 8, 9, 10, 11],
+    dram: &[
+        &[(1, 0)],
+        &[(1, 6)],
+        &[(4, 0)],
+        &[(4, 6)],
+        &[(7, 0)],
+        &[(7, 6)],
+        &[(10, 0)],
+        &[(10, 6)],
+    ],
+    eth: &[],
+    pcie: &[(0, 4)],
+    arc: &[(0, 2)],
+    harvest_rows: &[5, 7, 4, 8, 3, 9, 2, 10, 1, 11],
+    translation: None,
+};
+
+/// The layout of the SPI flash of Grayskull boards.
+///
+/// The last 64 KiB hold the board identity and calibration. The buffer of the
+/// firmware is in the ARC CSM, which BAR0 maps at `0x1FE0_0000` (as on
+/// Wormhole).
+///
+/// The sizes and the CSM window follow the public board documentation.
+const FLASH: FlashLayout = FlashLayout {
+    size: 0x0080_0000,
+    block_size: 0x1000,
+    writable: (0, 0x007F_0000),
+    buffer: Some(BufferWindow {
+        bar0: arc::CSM_BAR0,
+        arc: arc::CSM_BASE,
+        size: arc::CSM_SIZE,
+    }),
+};
+
+/// The power states of Grayskull firmware: A-state 0 while busy, A-state 3
+/// while idle (as on Wormhole).
+const POWER_STATES: PowerStates = PowerStates {
+    busy: ArcMsg::AState0,
+    idle: ArcMsg::AState3,
 };
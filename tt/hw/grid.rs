@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::kernel::prelude::*;
+
+use crate::tt::uapi::Harvesting;
+
+/// A coordinate system for the cores of a chip.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+#[cfg_attr(not(CONFIG_DEBUG_FS), allow(dead_code, reason = "cfg"))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Coords {
+    /// The coordinates on NOC0.
+    Noc0,
+    /// The coordinates on NOC1, which runs in the opposite direction (so that
+    /// both axes are mirrored).
+    Noc1,
+    /// The translated coordinates (see [`Grid::translation`]).
+    Translated,
+}
+
+/// The kind of a core.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CoreKind {
+    /// A Tensix (compute) core.
+    Tensix,
+    /// A DRAM core.
+    Dram,
+    /// An Ethernet core.
+    Eth,
+    /// A PCIe core.
+    Pcie,
+    /// An ARC (management) core.
+    Arc,
+}
+
+/// A core of a [`Grid`].
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+#[derive(Clone, Copy)]
+pub struct Core {
+    /// The kind of the core.
+    pub kind: CoreKind,
+    /// The unit of the core among those of its kind: the index in the Tensix
+    /// grid (by row, then column), the DRAM bank, the Ethernet port, or the
+    /// index of a PCIe or ARC core.
+    pub index: usize,
+    /// The NOC0 X coordinate.
+    pub x: u8,
+    /// The NOC0 Y coordinate.
+    pub y: u8,
+}
+
+/// The translated coordinates of the cores of a kind.
+///
+/// The cores are numbered in the order of their [`Grid`] description, and laid
+/// out from `origin` down each column of `rows` rows, then across.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct Translation {
+    /// The kind of the cores.
+    pub kind: CoreKind,
+    /// The translated coordinates of the first core.
+    pub origin: (u8, u8),
+    /// The number of rows of each column.
+    pub rows: u8,
+}
+
+/// The grid of cores of a chip, as seen from NOC0.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct Grid {
+    /// The number of columns.
+    pub width: u8,
+    /// The number of rows.
+    pub height: u8,
+    /// The X coordinates of the columns holding Tensix cores (ascending).
+    pub tensix_cols: &'static [u8],
+    /// The Y coordinates of the rows holding Tensix cores (ascending).
+    pub tensix_rows: &'static [u8],
+    /// The cores of each DRAM bank.
+    pub dram: &'static [&'static [(u8, u8)]],
+    /// The Ethernet cores, by port.
+    pub eth: &'static [(u8, u8)],
+    /// The PCIe cores.
+    pub pcie: &'static [(u8, u8)],
+    /// The ARC cores.
+    pub arc: &'static [(u8, u8)],
+    /// The Y coordinate of the row disabled by each bit of the harvesting mask
+    /// reported by the firmware (empty unless the chip is harvested by row).
+    pub harvest_rows: &'static [u8],
+    /// The translated coordinates of the kinds of cores which are moved by
+    /// translation, if the chip translates coordinates. Cores of other kinds
+    /// keep their NOC0 coordinates.
+    pub translation: Option<&'static [Translation]>,
+}
+
+impl Grid {
+    /// The grid of a chip of unknown architecture (without any cores).
+    pub(crate) const EMPTY: Self = Self {
+        width: 0,
+        height: 0,
+        tensix_cols: &[],
+        tensix_rows: &[],
+        dram: &[],
+        eth: &[],
+        pcie: &[],
+        arc: &[],
+        harvest_rows: &[],
+        translation: None,
+    };
+
+    /// Returns the cores of the grid: the Tensix cores, followed by the DRAM,
+    /// Ethernet, PCIe and ARC cores.
+    pub(crate) fn cores(&self) -> impl Iterator<Item = Core> + '_ {
+        let cols = self.tensix_cols.len();
+        let tensix = self.tensix_rows.iter().enumerate().flat_map(move |(row, &y)| {
+            let index = row * cols;
+            (index ..).zip(self.tensix_cols).map(move |(index, &x)| Core {
+                kind: CoreKind::Tensix,
+                index,
+                x,
+                y,
+            })
+        });
+        let dram = self.dram.iter().enumerate().flat_map(|(bank, cores)| {
+            cores.iter().map(move |&(x, y)| Core {
+                kind: CoreKind::Dram,
+                index: bank,
+                x,
+                y,
+            })
+        });
+        let others = [
+            (CoreKind::Eth, self.eth),
+            (CoreKind::Pcie, self.pcie),
+            (CoreKind::Arc, self.arc),
+        ];
+        let others = others.into_iter().flat_map(|(kind, cores)| {
+            cores
+                .iter()
+                .enumerate()
+                .map(move |(index, &(x, y))| Core { kind, index, x, y })
+        });
+        tensix.chain(dram).chain(others)
+    }
+
+    /// Returns the core at the NOC0 coordinates (`x`, `y`), if any.
+    pub(crate) fn core_at(&self, x: u8, y: u8) -> Option<Core> {
+        self.cores().find(|core| core.x == x && core.y == y)
+    }
+
+    /// Returns the translated coordinates of `core`, if the chip translates
+    /// coordinates.
+    pub(crate) fn translated(&self, core: &Core) -> Option<(u8, u8)> {
+        let translation = self.translation?;
+        let Some(rule) = translation.iter().find(|rule| rule.kind == core.kind) else {
+            return Some((core.x, core.y));
+        };
+        let seq = self
+            .cores()
+            .filter(|other| other.kind == core.kind)
+            .position(|other| other.x == core.x && other.y == core.y)?;
+        let rows = usize::from(rule.rows);
+        let x = usize::from(rule.origin.0) + seq.checked_div(rows)?;
+        let y = usize::from(rule.origin.1) + seq.checked_rem(rows)?;
+        Some((u8::try_from(x).ok()?, u8::try_from(y).ok()?))
+    }
+
+    /// Converts the coordinates (`x`, `y`) in `coords` to NOC0 coordinates.
+    ///
+    /// Returns `None` if the coordinates are outside of the grid, or (for
+    /// translated coordinates) do not translate a core.
+    pub(crate) fn to_noc0(&self, coords: Coords, x: u8, y: u8) -> Option<(u8, u8)> {
+        let inside = x < self.width && y < self.height;
+        match coords {
+            Coords::Noc0 => inside.then_some((x, y)),
+            Coords::Noc1 => inside.then(|| (self.width - 1 - x, self.height - 1 - y)),
+            Coords::Translated => {
+                let core = self.cores().find(|core| self.translated(core) == Some((x, y)))?;
+                Some((core.x, core.y))
+            },
+        }
+    }
+
+    /// Returns the number of columns and rows spanned by `coords`.
+    #[cfg_attr(not(CONFIG_DEBUG_FS), allow(dead_code, reason = "cfg"))]
+    pub(crate) fn extent(&self, coords: Coords) -> (u8, u8) {
+        match coords {
+            Coords::Noc0 | Coords::Noc1 => (self.width, self.height),
+            Coords::Translated => self
+                .cores()
+                .filter_map(|core| self.translated(&core))
+                .fold((0, 0), |(width, height), (x, y)| {
+                    (width.max(x.saturating_add(1)), height.max(y.saturating_add(1)))
+                }),
+        }
+    }
+
+    /// Returns `true` if `core` is disabled by `harvesting`.
+    pub(crate) fn is_disabled(&self, core: &Core, harvesting: &Harvesting) -> bool {
+        let bit = |mask: u32, index: usize| {
+            u32::try_from(index)
+                .ok()
+                .and_then(|index| mask.checked_shr(index))
+                .is_some_and(|mask| mask & 1 != 0)
+        };
+        let cols = self.tensix_cols.len().max(1);
+        match core.kind {
+            CoreKind::Tensix => {
+                bit(harvesting.tensix_rows, core.index / cols) || bit(harvesting.tensix_cols, core.index % cols)
+            },
+            CoreKind::Dram => bit(harvesting.dram_banks, core.index),
+            CoreKind::Eth => bit(harvesting.eth_cores, core.index),
+            CoreKind::Pcie | CoreKind::Arc => false,
+        }
+    }
+
+    /// Validates the target (`x`, `y`) in `coords` of a TLB window and returns
+    /// the targeted core.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EINVAL` if there is no core at the coordinates, or the
+    ///   core is disabled by `harvesting` (if given).
+    pub(crate) fn tlb_target(&self, coords: Coords, x: u8, y: u8, harvesting: Option<&Harvesting>) -> Result<Core> {
+        let (x, y) = self.to_noc0(coords, x, y).ok_or(EINVAL)?;
+        let core = self.core_at(x, y).ok_or(EINVAL)?;
+        if harvesting.is_some_and(|harvesting| self.is_disabled(&core, harvesting)) {
+            return Err(EINVAL);
+        }
+        Ok(core)
+    }
+
+    /// Decodes the harvesting mask `raw` reported by the firmware (see
+    /// [`Grid::harvest_rows`]).
+    pub(crate) fn harvesting(&self, raw: u32) -> Harvesting {
+        let mut tensix_rows = 0u32;
+        let disabled = (0u32 ..)
+            .zip(self.harvest_rows)
+            .filter(|&(bit, _y)| raw & (1 << bit) != 0);
+        for (_bit, y) in disabled {
+            if let Some(row) = self.tensix_rows.iter().position(|row| row == y) {
+                tensix_rows |= 1 << row;
+            }
+        }
+        Harvesting {
+            tensix_rows,
+            tensix_row_count: u32::try_from(self.tensix_rows.len()).unwrap_or(0),
+            tensix_col_count: u32::try_from(self.tensix_cols.len()).unwrap_or(0),
+            dram_bank_count: u32::try_from(self.dram.len()).unwrap_or(0),
+            eth_core_count: u32::try_from(self.eth.len()).unwrap_or(0),
+            raw,
+            ..Harvesting::default()
+        }
+    }
+}
+
+#[cfg(CONFIG_KUNIT)]
+#[::kernel::macros::kunit_tests(tt_core_grid)]
+mod tests {
+    use ::kernel::prelude::*;
+
+    use super::{Coords, Grid};
+    use crate::tt::{
+        hw::{HWCONFIG_BLACKHOLE, HWCONFIG_GRAYSKULL, HWCONFIG_WORMHOLE},
+        uapi::Harvesting,
+    };
+
+    /// The grids of the known architectures.
+    const GRIDS: [&Grid; 3] = [HWCONFIG_GRAYSKULL.grid, HWCONFIG_WORMHOLE.grid, HWCONFIG_BLACKHOLE.grid];
+
+    #[test]
+    fn cores_are_distinct_and_inside() {
+        for grid in GRIDS {
+            for (index, core) in grid.cores().enumerate() {
+                assert!(core.x < grid.width && core.y < grid.height);
+                let first = grid.cores().position(|other| other.x == core.x && other.y == core.y);
+                assert_eq!(first, Some(index));
+            }
+        }
+    }
+
+    #[test]
+    fn noc1_mirrors_noc0() {
+        for grid in GRIDS {
+            for core in grid.cores() {
+                let (x, y) = (grid.width - 1 - core.x, grid.height - 1 - core.y);
+                assert_eq!(grid.to_noc0(Coords::Noc1, x, y), Some((core.x, core.y)));
+            }
+            assert_eq!(grid.to_noc0(Coords::Noc1, grid.width, 0), None);
+        }
+    }
+
+    #[test]
+    fn translation_round_trips() {
+        for grid in GRIDS {
+            for core in grid.cores() {
+                let Some((x, y)) = grid.translated(&core) else {
+                    assert!(grid.translation.is_none());
+                    continue;
+                };
+                assert_eq!(grid.to_noc0(Coords::Translated, x, y), Some((core.x, core.y)));
+            }
+        }
+        let dram = HWCONFIG_BLACKHOLE.grid.core_at(0, 0);
+        assert_eq!(
+            dram.and_then(|core| HWCONFIG_BLACKHOLE.grid.translated(&core)),
+            Some((17, 12))
+        );
+    }
+
+    #[test]
+    fn harvesting_decodes_rows() {
+        // Bit 0 of the Wormhole mask disables the row at Y = 11, the last one.
+        let harvesting = HWCONFIG_WORMHOLE.grid.harvesting(0b1);
+        assert_eq!(harvesting.tensix_rows, 1 << 9);
+        assert_eq!(harvesting.tensix_row_count, 10);
+        assert_eq!(harvesting.tensix_col_count, 8);
+        assert_eq!(harvesting.dram_bank_count, 6);
+        assert_eq!(harvesting.eth_core_count, 16);
+    }
+
+    #[test]
+    fn tlb_targets() {
+        let grid = HWCONFIG_WORMHOLE.grid;
+        assert!(grid.tlb_target(Coords::Noc0, 1, 11, None).is_ok());
+        assert_eq!(grid.tlb_target(Coords::Noc0, 0, 2, None).err(), Some(EINVAL));
+        assert_eq!(grid.tlb_target(Coords::Noc0, 10, 0, None).err(), Some(EINVAL));
+        assert_eq!(grid.tlb_target(Coords::Translated, 1, 1, None).err(), Some(EINVAL));
+
+        let harvesting = grid.harvesting(0b1);
+        assert_eq!(
+            grid.tlb_target(Coords::Noc0, 1, 11, Some(&harvesting)).err(),
+            Some(EINVAL)
+        );
+        assert!(grid.tlb_target(Coords::Noc0, 1, 10, Some(&harvesting)).is_ok());
+        let unused = Harvesting::default();
+        assert!(grid.tlb_target(Coords::Noc0, 1, 11, Some(&unused)).is_ok());
+    }
+}
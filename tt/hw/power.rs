@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: The limits are enforced by the ARC firmware, which is told the limits
+// set by the driver (see `ArcMsg::SetAiclkRange`, `ArcMsg::SetPowerLimit` and
+// `ArcMsg::SetThermalLimit`). The driver only keeps them within the bounds of
+// the board. The message codes follow the public tools.
+
+use ::kernel::prelude::*;
+
+use crate::tt::{
+    hw::arc::ArcMsg,
+    uapi::{PowerInfo, PowerLimits},
+};
+
+/// The messages moving the ARC firmware of an architecture between its power
+/// states, as hinted by userspace.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct PowerStates {
+    /// The message to leave the low-power state (ramping up the clocks) when
+    /// the chip is about to be busy.
+    pub busy: ArcMsg,
+    /// The message to enter the low-power state once the chip is idle.
+    pub idle: ArcMsg,
+}
+
+/// The bounds of the power and clock limits of a board, as `(min, max)`.
+///
+/// The defaults are the widest AI clock range and the highest power and
+/// thermal limits.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct PowerBounds {
+    /// The AI clock in MHz.
+    pub aiclk: (u32, u32),
+    /// The power limit of the chip in watts.
+    pub power: (u32, u32),
+    /// The thermal limit of the chip in degrees Celsius.
+    pub thermal: (u32, u32),
+}
+
+impl PowerBounds {
+    /// Returns the default limits.
+    pub(crate) const fn defaults(&self) -> PowerLimits {
+        PowerLimits {
+            aiclk_min: self.aiclk.0,
+            aiclk_max: self.aiclk.1,
+            power_limit: self.power.1,
+            thermal_limit: self.thermal.1,
+        }
+    }
+
+    /// Returns the lower bounds of the limits.
+    const fn min(&self) -> PowerLimits {
+        PowerLimits {
+            aiclk_min: self.aiclk.0,
+            aiclk_max: self.aiclk.0,
+            power_limit: self.power.0,
+            thermal_limit: self.thermal.0,
+        }
+    }
+
+    /// Returns the upper bounds of the limits.
+    const fn max(&self) -> PowerLimits {
+        PowerLimits {
+            aiclk_min: self.aiclk.1,
+            aiclk_max: self.aiclk.1,
+            power_limit: self.power.1,
+            thermal_limit: self.thermal.1,
+        }
+    }
+
+    /// Returns the bounds as reported to userspace, with the current `limits`.
+    pub(crate) const fn info(&self, limits: PowerLimits) -> PowerInfo {
+        PowerInfo {
+            limits,
+            min: self.min(),
+            max: self.max(),
+            defaults: self.defaults(),
+        }
+    }
+
+    /// Checks that `limits` may be set.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `ERANGE` if a limit is not within the bounds.
+    /// * Errors with `EINVAL` if `aiclk_min` exceeds `aiclk_max`.
+    pub(crate) fn check(&self, limits: &PowerLimits) -> Result {
+        let within = |value: u32, (min, max): (u32, u32)| (min ..= max).contains(&value);
+        let valid = within(limits.aiclk_min, self.aiclk)
+            && within(limits.aiclk_max, self.aiclk)
+            && within(limits.power_limit, self.power)
+            && within(limits.thermal_limit, self.thermal);
+        if !valid {
+            return Err(ERANGE);
+        }
+        if limits.aiclk_min > limits.aiclk_max {
+            return Err(EINVAL);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(CONFIG_KUNIT)]
+#[::kernel::macros::kunit_tests(tt_core_power)]
+mod tests {
+    use ::kernel::prelude::*;
+
+    use crate::tt::{
+        hw::{HWCONFIG_BLACKHOLE, HWCONFIG_WORMHOLE},
+        uapi::PowerLimits,
+    };
+
+    #[test]
+    fn defaults_are_valid() {
+        for hw in [HWCONFIG_WORMHOLE, HWCONFIG_BLACKHOLE] {
+            for board in hw.boards {
+                let bounds = &board.power;
+                assert!(bounds.aiclk.0 <= bounds.aiclk.1);
+                assert!(bounds.power.0 <= bounds.power.1);
+                assert!(bounds.thermal.0 <= bounds.thermal.1);
+                assert!(bounds.check(&bounds.defaults()).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn limits() -> Result {
+        let board = HWCONFIG_WORMHOLE.boards.first().ok_or(ENODEV)?;
+        let bounds = &board.power;
+        let defaults = bounds.defaults();
+        let pinned = PowerLimits {
+            aiclk_min: bounds.aiclk.1,
+            ..defaults
+        };
+        assert!(bounds.check(&pinned).is_ok());
+        let inverted = PowerLimits {
+            aiclk_min: bounds.aiclk.1,
+            aiclk_max: bounds.aiclk.0,
+            ..defaults
+        };
+        assert_eq!(bounds.check(&inverted).err(), Some(EINVAL));
+        let fast = PowerLimits {
+            aiclk_max: bounds.aiclk.1 + 1,
+            ..defaults
+        };
+        assert_eq!(bounds.check(&fast).err(), Some(ERANGE));
+        let low = PowerLimits {
+            power_limit: bounds.power.0 - 1,
+            ..defaults
+        };
+        assert_eq!(bounds.check(&low).err(), Some(ERANGE));
+        let hot = PowerLimits {
+            thermal_limit: bounds.thermal.1 + 1,
+            ..defaults
+        };
+        assert_eq!(bounds.check(&hot).err(), Some(ERANGE));
+        Ok(())
+    }
+}
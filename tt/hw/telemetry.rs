@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: The ARC firmware of Wormhole keeps a table of telemetry words in its
+// CSM and reports its address (see `ArcMsg::GetTelemetry`). The table is read
+// through the CSM window of BAR0.
+
+use ::kernel::prelude::*;
+
+use crate::tt::hw::RegIo;
+
+/// The maximum number of words of a telemetry table.
+pub(crate) const MAX_WORDS: usize = 64;
+
+/// The layout of the telemetry table of the ARC firmware of an architecture.
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct TelemetryLayout {
+    /// The number of words of the table (at most [`MAX_WORDS`]).
+    pub words: usize,
+    /// The index of the heartbeat: a word the firmware increments
+    /// periodically while it runs, if there is one.
+    pub heartbeat: Option<usize>,
+    /// The index of the throttler: a word whose bits the firmware sets while
+    /// it throttles the chip (one bit per limit reached, e.g. the thermal
+    /// limit), if there is one.
+    pub throttler: Option<usize>,
+    /// The indices of the high and low words of the board ID (which encodes
+    /// the board type and serial number), if known.
+    pub board_id: Option<(usize, usize)>,
+    /// The indices of the AI, AXI and ARC clocks in MHz (in the low 16 bits of
+    /// each word), if known.
+    pub clocks: Option<(usize, usize, usize)>,
+    /// The indices of the versions of the ARC, Ethernet, M3 bootloader and M3
+    /// application firmware, if known.
+    pub fw_versions: Option<(usize, usize, usize, usize)>,
+}
+
+impl TelemetryLayout {
+    /// Returns the size of the table in bytes.
+    pub(crate) fn size(&self) -> Result<u32> {
+        Ok(u32::try_from(self.words)? * 4)
+    }
+}
+
+/// A snapshot of the telemetry table of a chip.
+pub(crate) struct Telemetry {
+    /// The words of the table (only the first `len` are valid).
+    words: [u32; MAX_WORDS],
+    /// The number of words read.
+    len: usize,
+}
+
+impl Telemetry {
+    /// Reads the table of `layout` at the BAR0 `offset`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if reading the table fails.
+    pub(crate) fn read<R: RegIo + ?Sized>(regs: &R, offset: usize, layout: &TelemetryLayout) -> Result<Self> {
+        let mut this = Self {
+            words: [0; MAX_WORDS],
+            len: layout.words.min(MAX_WORDS),
+        };
+        for (reg, word) in (offset ..).step_by(4).zip(&mut this.words[.. this.len]) {
+            *word = regs.read_reg(reg)?;
+        }
+        Ok(this)
+    }
+
+    /// Returns the words of the table.
+    pub(crate) fn words(&self) -> &[u32] {
+        &self.words[.. self.len]
+    }
+
+    /// Returns the word at `index`.
+    ///
+    /// # Errors
+    ///
+    /// * Errors with `EOPNOTSUPP` if `index` is beyond the words read.
+    pub(crate) fn word(&self, index: usize) -> Result<u32> {
+        self.words().get(index).copied().ok_or(EOPNOTSUPP)
+    }
+}
@@ -2,11 +2,172 @@
 
 use ::kernel::c_str;
 
-use crate::tt::hw::HwConfig;
+use crate::tt::hw::{
+    Arch,
+    Board,
+    HwConfig,
+    arc::{self, ArcMsg},
+    flash::{BufferWindow, FlashLayout},
+    fw::{FwImage, FwTarget},
+    grid::Grid,
+    power::{PowerBounds, PowerStates},
+    telemetry::TelemetryLayout,
+};
+
+/// Definitions related to the Ethernet cores and the links they train.
+pub(crate) mod eth;
+
+/// Definitions related to the TLB windows of BAR0.
+pub(crate) mod tlb;
 
 /// The Tenstorrent Wormhole hardware configuration description.
 pub(crate) const HWCONFIG_WORMHOLE: HwConfig = HwConfig {
     vendor_id: crate::tt::hw::PCI_VENDOR_ID_TENSTORRENT,
     device_id: 0x401E,
     name: c_str!("Wormhole"),
+    arch: Arch::Wormhole,
+    boards: &BOARDS,
+    grid: &GRID,
+    flash: Some(&FLASH),
+    firmware: &FIRMWARE,
+    telemetry: Some(&TELEMETRY),
+    power_states: Some(&POWER_STATES),
+};
+
+/// The known Wormhole boards.
+const BOARDS: [Board; 3] = [
+    Board {
+        subsystem_device_id: 0x0014,
+        name: c_str!("n150"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (50, 160),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0018,
+        name: c_str!("n300"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (50, 150),
+            thermal: THERMAL,
+        },
+    },
+    Board {
+        subsystem_device_id: 0x0035,
+        name: c_str!("Galaxy"),
+        power: PowerBounds {
+            aiclk: AICLK,
+            power: (50, 100),
+            thermal: THERMAL,
+        },
+    },
+];
+
+/// The bounds of the AI clock of Wormhole chips in MHz.
+///
+/// The bounds of the boards follow the public board specifications (the power
+/// limits are per chip, so half the board power of the n300).
+const AICLK: (u32, u32) = (500, 1000);
+
+/// The bounds of the thermal limit of Wormhole chips in degrees Celsius.
+const THERMAL: (u32, u32) = (50, 85);
+
+/// The Wormhole grid.
+///
+/// Harvested chips have Tensix rows disabled. The firmware reports them in the
+/// order of [`Grid::harvest_rows`]. Coordinates are not translated.
+const GRID: Grid = Grid {
+    width: 10,
+    height: 12,
+    tensix_cols: &[1, 2, 3, 4, 6, 7, 8, 9],
+    tensix_rows: &[1, 2, 3, 4, 5, 7, 8, 9, 10, 11],
+    dram: &[
+        &[(0, 0), (0, 1), (0, 11)],
+        &[(0, 5), (0, 6), (0, 7)],
+        &[(5, 0), (5, 1), (5, 11)],
+        &[(5, 2), (5, 9), (5, 10)],
+        &[(5, 3), (5, 4), (5, 8)],
+        &[(5, 5), (5, 6), (5, 7)],
+    ],
+    eth: &[
+        (9, 0),
+        (1, 0),
+        (8, 0),
+        (2, 0),
+        (7, 0),
+        (3, 0),
+        (6, 0),
+        (4, 0),
+        (9, 6),
+        (1, 6),
+        (8, 6),
+        (2, 6),
+        (7, 6),
+        (3, 6),
+        (6, 6),
+        (4, 6),
+    ],
+    pcie: &[(0, 3)],
+    arc: &[(0, 10)],
+    harvest_rows: &[11, 1, 10, 2, 9, 3, 8, 4, 7, 5],
+    translation: None,
+};
+
+/// The layout of the SPI flash of Wormhole boards.
+///
+/// The last 64 KiB hold the board identity and calibration. The buffer of the
+/// firmware is in the ARC CSM, which BAR0 maps at `0x1FE0_0000`.
+///
+/// The sizes and the CSM window follow the public board documentation.
+const FLASH: FlashLayout = FlashLayout {
+    size: 0x0100_0000,
+    block_size: 0x1000,
+    writable: (0, 0x00FF_0000),
+    buffer: Some(BufferWindow {
+        bar0: arc::CSM_BAR0,
+        arc: arc::CSM_BASE,
+        size: arc::CSM_SIZE,
+    }),
+};
+
+/// The firmware images loaded into Wormhole chips.
+///
+/// The Ethernet firmware reports the topology from version 6.0 (see
+/// [`eth::discover`]).
+const FIRMWARE: [FwImage; 1] = [FwImage {
+    name: c_str!("erisc"),
+    path: c_str!("tenstorrent/wormhole/erisc.bin"),
+    min_version: 0x0600_0000,
+    target: FwTarget::EthL1,
+    size: 0x0004_0000,
+}];
+
+/// The layout of the telemetry table of Wormhole firmware.
+///
+/// The heartbeat is the health counter of ARC core 0 (`ARC0_HEALTH`), the
+/// throttler is `THROTTLER`, the board ID is `BOARD_ID_HIGH` and
+/// `BOARD_ID_LOW`, the clocks are `AICLK`, `AXICLK` and `ARCCLK`, and the
+/// firmware versions are `ARC0_FW_VERSION`, `ETH_FW_VERSION`,
+/// `M3_BL_FW_VERSION` and `M3_APP_FW_VERSION`.
+///
+/// The indices follow the telemetry table of the public tools.
+///
+/// NOTE: The table has grown across firmware releases. The words beyond those
+/// of a release read as whatever follows the table in the CSM.
+const TELEMETRY: TelemetryLayout = TelemetryLayout {
+    words: 64,
+    heartbeat: Some(20),
+    throttler: Some(28),
+    board_id: Some((4, 5)),
+    clocks: Some((25, 26, 27)),
+    fw_versions: Some((6, 11, 12, 13)),
+};
+
+/// The power states of Wormhole firmware: A-state 0 while busy, A-state 3
+/// while idle.
+const POWER_STATES: PowerStates = PowerStates {
+    busy: ArcMsg::AState0,
+    idle: ArcMsg::AState3,
 };
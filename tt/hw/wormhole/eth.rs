@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::kernel::prelude::*;
+
+use crate::tt::{
+    hw::{
+        RegIo,
+        wormhole::{
+            GRID,
+            tlb::{noc_read32, noc_write, noc_write32},
+        },
+    },
+    uapi::{EthCoord, EthLink, Topology},
+};
+
+// NOTE: The addresses below are in the L1 of each Ethernet core, as laid out by
+// the Ethernet firmware (6.0 and later) once the ports have trained.
+
+/// The address of the node information, whose third word holds the coordinates
+/// of the chip (see [`coord`]).
+const NODE_INFO: u64 = 0x1100;
+
+/// The address of the connection status of the ports (one word per port).
+const ETH_CONN_INFO: u64 = 0x1200;
+
+/// The address of the training results.
+const RESULTS_BUF: u64 = 0x1EC0;
+
+/// The address of the information about the peer of the port (within the
+/// training results): the board ID (low and high words), the coordinates (see
+/// [`coord`]), and the port of the peer (in the fifth word).
+const REMOTE_INFO: u64 = RESULTS_BUF + 4 * 72;
+
+/// The address of the soft reset register of the RISC-V core.
+const SOFT_RESET: u64 = 0xFFB1_21B0;
+
+/// The soft reset bit of the RISC-V core of an Ethernet core.
+const SOFT_RESET_ERISC: u32 = 1 << 11;
+
+/// The connection status of a port which has not finished training.
+const STATUS_UNKNOWN: u32 = 0;
+
+/// The connection status of a port which is not connected.
+const STATUS_UNCONNECTED: u32 = 1;
+
+/// Decodes coordinates packed (from the low byte) as rack, shelf, X and Y.
+fn coord(word: u32) -> EthCoord {
+    let [rack, shelf, x, y] = word.to_le_bytes();
+    EthCoord { rack, shelf, x, y }
+}
+
+/// Reads the coordinates of the chip and the state of its links from the
+/// Ethernet cores.
+///
+/// The caller must serialize uses of the window reserved for the driver (see
+/// [`noc_read32`]).
+///
+/// # Errors
+///
+/// * Errors if reading from an Ethernet core fails.
+pub(crate) fn discover<R: RegIo + ?Sized>(regs: &R) -> Result<Topology> {
+    let mut topology = Topology::default();
+    let (x, y) = GRID.eth[0];
+    topology.local = coord(noc_read32(regs, x, y, NODE_INFO + 8)?);
+    topology.link_count = u32::try_from(GRID.eth.len()).map_err(|_err| EINVAL)?;
+
+    for (port, (link, &(x, y))) in (0u64 ..).zip(topology.links.iter_mut().zip(GRID.eth)) {
+        let status = noc_read32(regs, x, y, ETH_CONN_INFO + 4 * port)?;
+        *link = match status {
+            STATUS_UNKNOWN => EthLink::default(),
+            STATUS_UNCONNECTED => EthLink {
+                state: 1,
+                ..EthLink::default()
+            },
+            _connected => {
+                let lo = noc_read32(regs, x, y, REMOTE_INFO)?;
+                let hi = noc_read32(regs, x, y, REMOTE_INFO + 4)?;
+                EthLink {
+                    state: 2,
+                    remote_port: noc_read32(regs, x, y, REMOTE_INFO + 16)?,
+                    remote_board_id: (u64::from(hi) << 32) | u64::from(lo),
+                    remote: coord(noc_read32(regs, x, y, REMOTE_INFO + 8)?),
+                    reserved: 0,
+                }
+            },
+        };
+    }
+
+    Ok(topology)
+}
+
+/// Writes `image` from `addr` of the L1 of every Ethernet core not disabled by
+/// harvesting (whose bits are set in `disabled`), holding its RISC-V core in
+/// reset meanwhile.
+///
+/// The caller must serialize uses of the window reserved for the driver (see
+/// [`noc_read32`]).
+///
+/// NOTE: The soft reset register and bit follow the public documentation of
+/// the Tensix RISC-V cores, which is not specific to Ethernet cores, so images
+/// are only loaded with the `firmware_autoload` parameter set.
+///
+/// # Errors
+///
+/// * Errors if writing to an Ethernet core fails.
+pub(crate) fn load<R: RegIo + ?Sized>(regs: &R, addr: u64, image: &[u8], disabled: u32) -> Result {
+    for (index, &(x, y)) in (0u32 ..).zip(GRID.eth) {
+        if disabled.checked_shr(index).is_some_and(|mask| mask & 1 != 0) {
+            continue;
+        }
+        noc_write32(regs, x, y, SOFT_RESET, SOFT_RESET_ERISC)?;
+        noc_write(regs, x, y, addr, image)?;
+        noc_write32(regs, x, y, SOFT_RESET, 0)?;
+    }
+    Ok(())
+}
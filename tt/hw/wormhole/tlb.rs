@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::kernel::prelude::*;
+
+use crate::tt::hw::{RegIo, grid::Coords, wormhole::GRID};
+
+/// The BAR0 offset of the TLB configuration registers (one 64-bit register per
+/// window).
+const TLB_CONFIG: usize = 0x1FC0_0000;
+
+/// The index of the first 16 MiB window (after 156 1 MiB and 10 2 MiB windows).
+const TLB_16M_FIRST: usize = 166;
+
+/// The BAR0 offset of the first 16 MiB window.
+const TLB_16M_BASE: usize = 0x0B00_0000;
+
+/// The size of a 16 MiB window.
+const TLB_16M_SIZE: usize = 1 << 24;
+
+/// The index of the window reserved for the driver (the last 16 MiB window).
+///
+/// NOTE: Windows are not yet given to userspace, but this one must never be.
+const KERNEL_TLB: usize = 185;
+
+/// The bit offset of the X coordinate in the configuration of a 16 MiB window.
+///
+/// The configuration holds the upper bits of the address (the local offset) at
+/// bit 0, followed by the target coordinates and the ordering mode.
+const X_END_SHIFT: u32 = 12;
+/// The bit offset of the Y coordinate (see [`X_END_SHIFT`]).
+const Y_END_SHIFT: u32 = 18;
+/// The bit offset of the ordering mode (see [`X_END_SHIFT`]).
+const ORDERING_SHIFT: u32 = 38;
+
+/// The strict ordering mode of a window (each access completes in order).
+const ORDERING_STRICT: u64 = 1;
+
+/// The size of the NOC address space of a core.
+const NOC_ADDR_LIMIT: u64 = 1 << 36;
+
+/// The owner of a TLB window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TlbOwner {
+    /// The window is not used.
+    Unused,
+    /// The window is reserved for the driver.
+    Kernel,
+}
+
+/// A run of TLB windows of the same size and owner.
+pub(crate) struct TlbRange {
+    /// The index of the first window.
+    pub(crate) first: usize,
+    /// The index of the last window.
+    pub(crate) last: usize,
+    /// The size of each window in bytes.
+    pub(crate) size: usize,
+    /// The owner of the windows.
+    pub(crate) owner: TlbOwner,
+}
+
+/// The allocation of the TLB windows, in order of index.
+///
+/// Windows are not yet given to userspace, so all but [`KERNEL_TLB`] are
+/// unused.
+pub(crate) const ALLOCATION: [TlbRange; 4] = [
+    TlbRange {
+        first: 0,
+        last: 155,
+        size: 1 << 20,
+        owner: TlbOwner::Unused,
+    },
+    TlbRange {
+        first: 156,
+        last: TLB_16M_FIRST - 1,
+        size: 2 << 20,
+        owner: TlbOwner::Unused,
+    },
+    TlbRange {
+        first: TLB_16M_FIRST,
+        last: KERNEL_TLB - 1,
+        size: TLB_16M_SIZE,
+        owner: TlbOwner::Unused,
+    },
+    TlbRange {
+        first: KERNEL_TLB,
+        last: KERNEL_TLB,
+        size: TLB_16M_SIZE,
+        owner: TlbOwner::Kernel,
+    },
+];
+
+/// Returns the target of the window reserved for the driver, as the NOC0
+/// coordinates (`x`, `y`) and the address of the start of the window.
+///
+/// The caller must serialize uses of the window.
+///
+/// # Errors
+///
+/// * Errors if reading the registers fails.
+pub(crate) fn kernel_target<R: RegIo + ?Sized>(regs: &R) -> Result<(u8, u8, u64)> {
+    let reg = TLB_CONFIG + KERNEL_TLB * 8;
+    let config = u64::from(regs.read_reg(reg)?) | (u64::from(regs.read_reg(reg + 4)?) << 32);
+    let coord = |shift: u32| u8::try_from((config >> shift) & 0x3F).map_err(|_err| EINVAL);
+    let addr = (config & ((1 << X_END_SHIFT) - 1)) << 24;
+    Ok((coord(X_END_SHIFT)?, coord(Y_END_SHIFT)?, addr))
+}
+
+/// Points the window reserved for the driver at `addr` of the core at NOC0
+/// coordinates (`x`, `y`), and returns the BAR0 offset of `addr`.
+///
+/// The caller must serialize uses of the window.
+///
+/// # Errors
+///
+/// * Errors with `EINVAL` if there is no core at the coordinates.
+/// * Errors with `EINVAL` if `addr` is misaligned or out of bounds.
+/// * Errors if accessing the registers fails.
+fn configure<R: RegIo + ?Sized>(regs: &R, x: u8, y: u8, addr: u64) -> Result<usize> {
+    GRID.tlb_target(Coords::Noc0, x, y, None)?;
+    if addr % 4 != 0 || addr >= NOC_ADDR_LIMIT {
+        return Err(EINVAL);
+    }
+
+    let config = (addr >> 24)
+        | (u64::from(x) << X_END_SHIFT)
+        | (u64::from(y) << Y_END_SHIFT)
+        | (ORDERING_STRICT << ORDERING_SHIFT);
+    let reg = TLB_CONFIG + KERNEL_TLB * 8;
+    regs.write_reg(reg, u32::try_from(config & 0xFFFF_FFFF).map_err(|_err| EINVAL)?)?;
+    regs.write_reg(reg + 4, u32::try_from(config >> 32).map_err(|_err| EINVAL)?)?;
+    // Read back the configuration so that it takes effect before the access.
+    regs.read_reg(reg)?;
+    regs.tlb_configured(KERNEL_TLB, x, y, addr & !0x00FF_FFFF);
+
+    let offset = usize::try_from(addr & 0x00FF_FFFF).map_err(|_err| EINVAL)?;
+    let window = TLB_16M_BASE + (KERNEL_TLB - TLB_16M_FIRST) * TLB_16M_SIZE;
+    Ok(window + offset)
+}
+
+/// Reads the word at `addr` of the core at NOC0 coordinates (`x`, `y`) through
+/// the window reserved for the driver.
+///
+/// The caller must serialize uses of the window.
+///
+/// # Errors
+///
+/// * Errors with `EINVAL` if there is no core at the coordinates.
+/// * Errors with `EINVAL` if `addr` is misaligned or out of bounds.
+/// * Errors if accessing the registers fails.
+pub(crate) fn noc_read32<R: RegIo + ?Sized>(regs: &R, x: u8, y: u8, addr: u64) -> Result<u32> {
+    let offset = configure(regs, x, y, addr)?;
+    regs.read_reg(offset)
+}
+
+/// Writes `value` to the word at `addr` of the core at NOC0 coordinates (`x`,
+/// `y`) through the window reserved for the driver.
+///
+/// The caller must serialize uses of the window.
+///
+/// # Errors
+///
+/// * Errors with `EINVAL` if there is no core at the coordinates.
+/// * Errors with `EINVAL` if `addr` is misaligned or out of bounds.
+/// * Errors if accessing the registers fails.
+pub(crate) fn noc_write32<R: RegIo + ?Sized>(regs: &R, x: u8, y: u8, addr: u64, value: u32) -> Result {
+    let offset = configure(regs, x, y, addr)?;
+    regs.write_reg(offset, value)
+}
+
+/// Writes `data` (a whole number of words) from `addr` of the core at NOC0
+/// coordinates (`x`, `y`) through the window reserved for the driver.
+///
+/// The caller must serialize uses of the window.
+///
+/// # Errors
+///
+/// * Errors with `EINVAL` if there is no core at the coordinates.
+/// * Errors with `EINVAL` if the range is misaligned or crosses a window.
+/// * Errors if accessing the registers fails.
+pub(crate) fn noc_write<R: RegIo + ?Sized>(regs: &R, x: u8, y: u8, addr: u64, data: &[u8]) -> Result {
+    let offset = configure(regs, x, y, addr)?;
+    let rel = usize::try_from(addr & 0x00FF_FFFF).map_err(|_err| EINVAL)?;
+    if data.len() % 4 != 0 || rel + data.len() > TLB_16M_SIZE {
+        return Err(EINVAL);
+    }
+    for (word, chunk) in (offset ..).step_by(4).zip(data.chunks_exact(4)) {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(chunk);
+        regs.write_reg(word, u32::from_le_bytes(bytes))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: GPL-2.0
+
+use ::core::mem::MaybeUninit;
+use ::kernel::{
+    bindings::{self, notifier_block},
+    ffi::{c_int, c_ulong, c_void},
+    prelude::*,
+    sync::Arc,
+    types::Opaque,
+};
+
+use crate::tt::device::chip::TtChip;
+
+/// The reboot notifier which puts all chips into a safe state before the
+/// system reboots, halts or powers off.
+///
+/// The notifier runs before the devices are shut down, so that every chip is
+/// told the host is going down even if its PCI shutdown callback is not called
+/// (e.g., for an emergency restart). The PCI shutdown callback then quiesces
+/// DMA for each bound device.
+pub(crate) struct TtReboot;
+
+impl Drop for TtReboot {
+    fn drop(&mut self) {
+        let nb = Opaque::raw_get(&raw const NOTIFIER);
+        // SAFETY: `nb` was registered in `TtReboot::new`.
+        unsafe { bindings::unregister_reboot_notifier(nb) };
+    }
+}
+
+impl TtReboot {
+    /// Creates a new [`TtReboot`] by registering the reboot notifier.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if `register_reboot_notifier` fails.
+    pub(crate) fn new() -> Result<Self> {
+        // SAFETY: `new` is only called once, from the module initializer.
+        unsafe { CHIPS.init() };
+
+        let nb = Opaque::raw_get(&raw const NOTIFIER);
+        // SAFETY: `nb` is a static and is unregistered on drop.
+        let err = unsafe { bindings::register_reboot_notifier(nb) };
+        ::kernel::error::to_result(err)?;
+
+        Ok(Self)
+    }
+
+    /// Returns the registered chips (those of every bound or emulated device).
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the list fails.
+    #[cfg_attr(not(CONFIG_DEBUG_FS), allow(dead_code, reason = "cfg"))]
+    pub(crate) fn chips() -> Result<KVec<Arc<TtChip>>> {
+        let chips = CHIPS.lock();
+        let mut list = KVec::with_capacity(chips.len(), GFP_KERNEL)?;
+        for chip in chips.iter() {
+            list.push(Arc::clone(chip), GFP_KERNEL)?;
+        }
+        Ok(list)
+    }
+
+    /// Puts all registered chips into a safe state.
+    extern "C" fn notify(_nb: *mut notifier_block, _action: c_ulong, _data: *mut c_void) -> c_int {
+        for chip in CHIPS.lock().iter() {
+            chip.shutdown(None);
+        }
+
+        #[allow(clippy::as_conversions, reason = "kernel constant")]
+        let done = bindings::NOTIFY_DONE as c_int;
+        done
+    }
+}
+
+/// The registration of a chip with the reboot notifier.
+pub(crate) struct RebootEntry {
+    /// The registered chip.
+    chip: Arc<TtChip>,
+}
+
+impl Drop for RebootEntry {
+    fn drop(&mut self) {
+        let chip = &self.chip;
+        CHIPS.lock().retain(|other| !Arc::ptr_eq(other, chip));
+    }
+}
+
+impl RebootEntry {
+    /// Registers `chip` with the reboot notifier.
+    ///
+    /// # Errors
+    ///
+    /// * Errors if allocating the entry fails.
+    pub(crate) fn new(chip: &Arc<TtChip>) -> Result<Self> {
+        let chip = Arc::clone(chip);
+        CHIPS.lock().push(Arc::clone(&chip), GFP_KERNEL)?;
+        Ok(Self { chip })
+    }
+}
+
+::kernel::sync::global_lock! {
+    // SAFETY: Initialized in `TtReboot::new` before first use.
+    unsafe(uninit) static CHIPS: Mutex<KVec<Arc<TtChip>>> = KVec::new();
+}
+
+/// The reboot notifier block.
+///
+/// NOTE: We use `Opaque` to prevent implicit copies.
+static mut NOTIFIER: Opaque<notifier_block> = Opaque::new(notifier_block {
+    notifier_call: Some(TtReboot::notify),
+    // SAFETY: All-zeroes is a valid bit pattern for the remaining fields.
+    ..unsafe { MaybeUninit::zeroed().assume_init() }
+});
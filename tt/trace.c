@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: GPL-2.0
+
+/*
+ * Tracepoints of the driver.
+ *
+ * The events are defined in C (see trace.h) since out-of-tree modules cannot
+ * extend the kernel bindings used by `kernel::tracepoint::declare_trace!`.
+ * The wrappers below are called from Rust (see trace.rs).
+ */
+
+#define CREATE_TRACE_POINTS
+#include "trace.h"
+
+void tt_trace_probe(u32 ordinal, u16 vendor_id, u16 device_id, const char *name, const char *board);
+void tt_trace_ordinal_free(u32 ordinal);
+void tt_trace_ioctl_enter(u32 ordinal, u32 cmd);
+void tt_trace_ioctl_exit(u32 ordinal, u32 cmd, int ret, s64 latency_ns);
+void tt_trace_arc_msg_send(u32 ordinal, u32 code, u32 arg);
+void tt_trace_arc_msg_reply(u32 ordinal, u32 code, int ret, u32 exit);
+void tt_trace_reset_start(u32 ordinal, u32 limit);
+void tt_trace_reset_finish(u32 ordinal, u32 attempts, int ret);
+void tt_trace_tlb_configure(u32 ordinal, u32 tlb, u8 x, u8 y, u64 addr);
+
+void tt_trace_probe(u32 ordinal, u16 vendor_id, u16 device_id, const char *name, const char *board)
+{
+	trace_tt_probe(ordinal, vendor_id, device_id, name, board);
+}
+
+void tt_trace_ordinal_free(u32 ordinal)
+{
+	trace_tt_ordinal_free(ordinal);
+}
+
+void tt_trace_ioctl_enter(u32 ordinal, u32 cmd)
+{
+	trace_tt_ioctl_enter(ordinal, cmd);
+}
+
+void tt_trace_ioctl_exit(u32 ordinal, u32 cmd, int ret, s64 latency_ns)
+{
+	trace_tt_ioctl_exit(ordinal, cmd, ret, latency_ns);
+}
+
+void tt_trace_arc_msg_send(u32 ordinal, u32 code, u32 arg)
+{
+	trace_tt_arc_msg_send(ordinal, code, arg);
+}
+
+void tt_trace_arc_msg_reply(u32 ordinal, u32 code, int ret, u32 exit)
+{
+	trace_tt_arc_msg_reply(ordinal, code, ret, exit);
+}
+
+void tt_trace_reset_start(u32 ordinal, u32 limit)
+{
+	trace_tt_reset_start(ordinal, limit);
+}
+
+void tt_trace_reset_finish(u32 ordinal, u32 attempts, int ret)
+{
+	trace_tt_reset_finish(ordinal, attempts, ret);
+}
+
+void tt_trace_tlb_configure(u32 ordinal, u32 tlb, u8 x, u8 y, u64 addr)
+{
+	trace_tt_tlb_configure(ordinal, tlb, x, y, addr);
+}
@@ -0,0 +1,174 @@
+/* SPDX-License-Identifier: GPL-2.0 */
+
+#undef TRACE_SYSTEM
+#define TRACE_SYSTEM tt_core
+
+#if !defined(_TT_TRACE_H) || defined(TRACE_HEADER_MULTI_READ)
+#define _TT_TRACE_H
+
+#include <linux/tracepoint.h>
+#include <linux/types.h>
+
+TRACE_EVENT(tt_probe,
+	TP_PROTO(u32 ordinal, u16 vendor_id, u16 device_id, const char *name, const char *board),
+	TP_ARGS(ordinal, vendor_id, device_id, name, board),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+		__field(u16, vendor_id)
+		__field(u16, device_id)
+		__string(name, name)
+		__string(board, board)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+		__entry->vendor_id = vendor_id;
+		__entry->device_id = device_id;
+		__assign_str(name);
+		__assign_str(board);
+	),
+	TP_printk("ordinal=%u vendor_id=%#06x device_id=%#06x name=%s board=%s",
+		  __entry->ordinal, __entry->vendor_id, __entry->device_id,
+		  __get_str(name), __get_str(board))
+);
+
+TRACE_EVENT(tt_ordinal_free,
+	TP_PROTO(u32 ordinal),
+	TP_ARGS(ordinal),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+	),
+	TP_printk("ordinal=%u", __entry->ordinal)
+);
+
+TRACE_EVENT(tt_ioctl_enter,
+	TP_PROTO(u32 ordinal, u32 cmd),
+	TP_ARGS(ordinal, cmd),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+		__field(u32, cmd)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+		__entry->cmd = cmd;
+	),
+	TP_printk("ordinal=%u cmd=%#x", __entry->ordinal, __entry->cmd)
+);
+
+TRACE_EVENT(tt_ioctl_exit,
+	TP_PROTO(u32 ordinal, u32 cmd, int ret, s64 latency_ns),
+	TP_ARGS(ordinal, cmd, ret, latency_ns),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+		__field(u32, cmd)
+		__field(int, ret)
+		__field(s64, latency_ns)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+		__entry->cmd = cmd;
+		__entry->ret = ret;
+		__entry->latency_ns = latency_ns;
+	),
+	TP_printk("ordinal=%u cmd=%#x ret=%d latency_ns=%lld",
+		  __entry->ordinal, __entry->cmd, __entry->ret, __entry->latency_ns)
+);
+
+TRACE_EVENT(tt_arc_msg_send,
+	TP_PROTO(u32 ordinal, u32 code, u32 arg),
+	TP_ARGS(ordinal, code, arg),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+		__field(u32, code)
+		__field(u32, arg)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+		__entry->code = code;
+		__entry->arg = arg;
+	),
+	TP_printk("ordinal=%u code=%#x arg=%#x",
+		  __entry->ordinal, __entry->code, __entry->arg)
+);
+
+TRACE_EVENT(tt_arc_msg_reply,
+	TP_PROTO(u32 ordinal, u32 code, int ret, u32 exit),
+	TP_ARGS(ordinal, code, ret, exit),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+		__field(u32, code)
+		__field(int, ret)
+		__field(u32, exit)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+		__entry->code = code;
+		__entry->ret = ret;
+		__entry->exit = exit;
+	),
+	TP_printk("ordinal=%u code=%#x ret=%d exit=%#x",
+		  __entry->ordinal, __entry->code, __entry->ret, __entry->exit)
+);
+
+TRACE_EVENT(tt_reset_start,
+	TP_PROTO(u32 ordinal, u32 limit),
+	TP_ARGS(ordinal, limit),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+		__field(u32, limit)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+		__entry->limit = limit;
+	),
+	TP_printk("ordinal=%u limit=%u", __entry->ordinal, __entry->limit)
+);
+
+TRACE_EVENT(tt_reset_finish,
+	TP_PROTO(u32 ordinal, u32 attempts, int ret),
+	TP_ARGS(ordinal, attempts, ret),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+		__field(u32, attempts)
+		__field(int, ret)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+		__entry->attempts = attempts;
+		__entry->ret = ret;
+	),
+	TP_printk("ordinal=%u attempts=%u ret=%d",
+		  __entry->ordinal, __entry->attempts, __entry->ret)
+);
+
+TRACE_EVENT(tt_tlb_configure,
+	TP_PROTO(u32 ordinal, u32 tlb, u8 x, u8 y, u64 addr),
+	TP_ARGS(ordinal, tlb, x, y, addr),
+	TP_STRUCT__entry(
+		__field(u32, ordinal)
+		__field(u32, tlb)
+		__field(u8, x)
+		__field(u8, y)
+		__field(u64, addr)
+	),
+	TP_fast_assign(
+		__entry->ordinal = ordinal;
+		__entry->tlb = tlb;
+		__entry->x = x;
+		__entry->y = y;
+		__entry->addr = addr;
+	),
+	TP_printk("ordinal=%u tlb=%u x=%u y=%u addr=%#llx",
+		  __entry->ordinal, __entry->tlb, __entry->x, __entry->y, __entry->addr)
+);
+
+#endif /* _TT_TRACE_H */
+
+#undef TRACE_INCLUDE_PATH
+#define TRACE_INCLUDE_PATH .
+#undef TRACE_INCLUDE_FILE
+#define TRACE_INCLUDE_FILE trace
+
+#include <trace/define_trace.h>
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: The events are defined in `trace.h` (system `tt_core`) and emitted
+// through the C wrappers in `trace.c`, since out-of-tree modules cannot extend
+// the bindings used by `::kernel::tracepoint::declare_trace!`. Each event
+// carries the ordinal of the misc device of the chip.
+
+use ::kernel::{
+    ffi::{c_char, c_int},
+    prelude::*,
+};
+
+unsafe extern "C" {
+    /// Emits `tt_probe`.
+    fn tt_trace_probe(ordinal: u32, vendor_id: u16, device_id: u16, name: *const c_char, board: *const c_char);
+    /// Emits `tt_ordinal_free`.
+    fn tt_trace_ordinal_free(ordinal: u32);
+    /// Emits `tt_ioctl_enter`.
+    fn tt_trace_ioctl_enter(ordinal: u32, cmd: u32);
+    /// Emits `tt_ioctl_exit`.
+    fn tt_trace_ioctl_exit(ordinal: u32, cmd: u32, ret: c_int, latency_ns: i64);
+    /// Emits `tt_arc_msg_send`.
+    fn tt_trace_arc_msg_send(ordinal: u32, code: u32, arg: u32);
+    /// Emits `tt_arc_msg_reply`.
+    fn tt_trace_arc_msg_reply(ordinal: u32, code: u32, ret: c_int, exit: u32);
+    /// Emits `tt_reset_start`.
+    fn tt_trace_reset_start(ordinal: u32, limit: u32);
+    /// Emits `tt_reset_finish`.
+    fn tt_trace_reset_finish(ordinal: u32, attempts: u32, ret: c_int);
+    /// Emits `tt_tlb_configure`.
+    fn tt_trace_tlb_configure(ordinal: u32, tlb: u32, x: u8, y: u8, addr: u64);
+}
+
+/// Returns `ordinal` as the `u32` recorded by events.
+fn ordinal_u32(ordinal: usize) -> u32 {
+    u32::try_from(ordinal).unwrap_or(u32::MAX)
+}
+
+/// Returns the errno of `res` (0 for success) as recorded by events.
+fn errno<T>(res: &Result<T>) -> c_int {
+    match *res {
+        Ok(_) => 0,
+        Err(err) => err.to_errno(),
+    }
+}
+
+/// Traces a device bound by probe.
+pub(crate) fn probe(ordinal: usize, vendor_id: u16, device_id: u16, name: &CStr, board: &CStr) {
+    // SAFETY: The strings are valid for the duration of the call.
+    unsafe {
+        tt_trace_probe(
+            ordinal_u32(ordinal),
+            vendor_id,
+            device_id,
+            name.as_char_ptr(),
+            board.as_char_ptr(),
+        )
+    };
+}
+
+/// Traces an ordinal returned to the pool.
+pub(crate) fn ordinal_free(ordinal: usize) {
+    // SAFETY: FFI call without preconditions.
+    unsafe { tt_trace_ordinal_free(ordinal_u32(ordinal)) };
+}
+
+/// Traces the entry of ioctl `cmd`.
+pub(crate) fn ioctl_enter(ordinal: usize, cmd: u32) {
+    // SAFETY: FFI call without preconditions.
+    unsafe { tt_trace_ioctl_enter(ordinal_u32(ordinal), cmd) };
+}
+
+/// Traces the exit of ioctl `cmd` after `latency_ns`.
+pub(crate) fn ioctl_exit(ordinal: usize, cmd: u32, res: &Result<isize>, latency_ns: i64) {
+    // SAFETY: FFI call without preconditions.
+    unsafe { tt_trace_ioctl_exit(ordinal_u32(ordinal), cmd, errno(res), latency_ns) };
+}
+
+/// Traces a message with `code` and `arg` being sent to the ARC firmware.
+pub(crate) fn arc_msg_send(ordinal: usize, code: u32, arg: u32) {
+    // SAFETY: FFI call without preconditions.
+    unsafe { tt_trace_arc_msg_send(ordinal_u32(ordinal), code, arg) };
+}
+
+/// Traces the reply to a message with `code` from the ARC firmware.
+pub(crate) fn arc_msg_reply(ordinal: usize, code: u32, res: &Result<u32>) {
+    let exit = res.as_ref().map_or(0, |exit| *exit);
+    // SAFETY: FFI call without preconditions.
+    unsafe { tt_trace_arc_msg_reply(ordinal_u32(ordinal), code, errno(res), exit) };
+}
+
+/// Traces the start of a reset of up to `limit` attempts.
+pub(crate) fn reset_start(ordinal: usize, limit: u32) {
+    // SAFETY: FFI call without preconditions.
+    unsafe { tt_trace_reset_start(ordinal_u32(ordinal), limit) };
+}
+
+/// Traces the end of a reset after `attempts` function resets.
+pub(crate) fn reset_finish(ordinal: usize, attempts: u32, res: &Result) {
+    // SAFETY: FFI call without preconditions.
+    unsafe { tt_trace_reset_finish(ordinal_u32(ordinal), attempts, errno(res)) };
+}
+
+/// Traces the TLB window `tlb` being pointed at `addr` of the core at NOC0
+/// coordinates (`x`, `y`).
+pub(crate) fn tlb_configure(ordinal: usize, tlb: usize, x: u8, y: u8, addr: u64) {
+    let tlb = u32::try_from(tlb).unwrap_or(u32::MAX);
+    // SAFETY: FFI call without preconditions.
+    unsafe { tt_trace_tlb_configure(ordinal_u32(ordinal), tlb, x, y, addr) };
+}
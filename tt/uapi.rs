@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: Numbers below `IOCTL_EXT_BASE` are reserved for the ioctls of the
+// official driver, which share `IOCTL_MAGIC`. Extensions of this driver are
+// numbered from `IOCTL_EXT_BASE`.
+//
+// NOTE: The definitions are mirrored by the uAPI tests in
+// `tools/uapi-test/src/uapi.rs`, which must be updated along with them.
+
+use ::kernel::{
+    ioctl::{_IOR, _IOW},
+    transmute::{AsBytes, FromBytes},
+};
+
+/// The ioctl type of the device nodes.
+const IOCTL_MAGIC: u32 = 0xFA;
+
+/// The first ioctl number of the extensions of this driver.
+const IOCTL_EXT_BASE: u32 = 0x80;
+
+/// Binds an eventfd to an interrupt source (see [`BindEventfd`]).
+pub(crate) const IOCTL_BIND_EVENTFD: u32 = _IOW::<BindEventfd>(IOCTL_MAGIC, IOCTL_EXT_BASE);
+
+/// Returns the Ethernet topology of the chip (see [`Topology`]).
+pub(crate) const IOCTL_GET_TOPOLOGY: u32 = _IOR::<Topology>(IOCTL_MAGIC, IOCTL_EXT_BASE + 1);
+
+/// Returns the harvesting of the chip (see [`Harvesting`]).
+pub(crate) const IOCTL_GET_HARVESTING: u32 = _IOR::<Harvesting>(IOCTL_MAGIC, IOCTL_EXT_BASE + 2);
+
+/// Returns the layout of the SPI flash of the board (see [`FlashInfo`]).
+pub(crate) const IOCTL_FLASH_INFO: u32 = _IOR::<FlashInfo>(IOCTL_MAGIC, IOCTL_EXT_BASE + 3);
+
+/// Reads from the SPI flash of the board (see [`FlashIo`]).
+pub(crate) const IOCTL_FLASH_READ: u32 = _IOW::<FlashIo>(IOCTL_MAGIC, IOCTL_EXT_BASE + 4);
+
+/// Erases blocks of the SPI flash of the board (see [`FlashIo`]).
+pub(crate) const IOCTL_FLASH_ERASE: u32 = _IOW::<FlashIo>(IOCTL_MAGIC, IOCTL_EXT_BASE + 5);
+
+/// Writes a signed bundle to the SPI flash of the board (see [`FlashIo`] and
+/// [`FlashBundle`]).
+pub(crate) const IOCTL_FLASH_WRITE: u32 = _IOW::<FlashIo>(IOCTL_MAGIC, IOCTL_EXT_BASE + 6);
+
+/// Returns the power and clock limits of the chip and the bounds of its board
+/// (see [`PowerInfo`]).
+pub(crate) const IOCTL_GET_POWER_LIMITS: u32 = _IOR::<PowerInfo>(IOCTL_MAGIC, IOCTL_EXT_BASE + 7);
+
+/// Sets the power and clock limits of the chip (see [`PowerLimits`]).
+pub(crate) const IOCTL_SET_POWER_LIMITS: u32 = _IOW::<PowerLimits>(IOCTL_MAGIC, IOCTL_EXT_BASE + 8);
+
+/// Hints whether the file is about to use the chip (see [`PowerState`]).
+pub(crate) const IOCTL_SET_POWER_STATE: u32 = _IOW::<PowerState>(IOCTL_MAGIC, IOCTL_EXT_BASE + 9);
+
+/// The argument of [`IOCTL_BIND_EVENTFD`].
+///
+/// The eventfd is signalled on every interrupt from `source` until it is
+/// unbound (by passing a negative `fd`) or the file which bound it is closed.
+/// Only one eventfd may be bound to a source at a time.
+///
+/// Sources are numbered as follows:
+///
+/// * 0: the ARC firmware replied to a message.
+/// * 1: the firmware reported a fault.
+///
+/// NOTE: The vectors of the sources are unverified, so binding fails with
+/// `ENXIO` unless the `unsafe_irq_sources` parameter is set.
+#[repr(C)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct BindEventfd {
+    /// The interrupt source.
+    pub source: u32,
+    /// The eventfd, or negative to unbind.
+    pub fd: i32,
+}
+
+// SAFETY: `BindEventfd` consists of integers only, so any bit pattern is valid.
+unsafe impl FromBytes for BindEventfd {}
+
+/// A device state change event, as read from the device nodes.
+///
+/// Reads return whole records only. Kinds are numbered as follows:
+///
+/// * 1: events were dropped since the queue was full (`data` is the count).
+/// * 2: a reset of the chip started.
+/// * 3: a reset of the chip completed (`data` is the errno, or 0 on success).
+/// * 4: a PCIe error was reported (`data` is the channel state: 1 for normal,
+///   2 for frozen and 3 for permanent failure).
+/// * 5: the firmware reported a fault.
+/// * 6: the firmware started throttling the chip, e.g. at its thermal limit
+///   (`data` is the throttler word of its telemetry).
+/// * 7: the chip is being suspended.
+/// * 8: the chip was resumed.
+/// * 9: the device was removed (no further events follow).
+/// * 10: the firmware heartbeat stopped (`data` is the time since it last
+///   changed in milliseconds).
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct Event {
+    /// The kind of event.
+    pub kind: u32,
+    /// The state generation of the chip when the event occurred.
+    pub generation: u32,
+    /// The time of the event (`CLOCK_MONOTONIC`) in nanoseconds.
+    pub timestamp_ns: u64,
+    /// The data of the event (depending on the kind).
+    pub data: u64,
+}
+
+// SAFETY: `Event` consists of integers only, without padding.
+unsafe impl AsBytes for Event {}
+
+/// The maximum number of Ethernet links of a chip.
+pub const MAX_ETH_LINKS: usize = 16;
+
+/// The coordinates of a chip in an Ethernet-connected system.
+///
+/// `x` and `y` locate the chip within its shelf (e.g., the position on a
+/// Galaxy), `rack` and `shelf` locate the shelf.
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct EthCoord {
+    /// The rack.
+    pub rack: u8,
+    /// The shelf within the rack.
+    pub shelf: u8,
+    /// The X coordinate within the shelf.
+    pub x: u8,
+    /// The Y coordinate within the shelf.
+    pub y: u8,
+}
+
+// SAFETY: `EthCoord` consists of integers only, without padding.
+unsafe impl AsBytes for EthCoord {}
+
+/// An Ethernet link of a chip, as reported by the firmware of its port.
+///
+/// States are numbered as follows:
+///
+/// * 0: unknown (the port has not finished training, or is not used).
+/// * 1: not connected.
+/// * 2: connected (the `remote_*` fields describe the peer).
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct EthLink {
+    /// The state of the link.
+    pub state: u32,
+    /// The port of the peer.
+    pub remote_port: u32,
+    /// The board ID of the peer.
+    pub remote_board_id: u64,
+    /// The coordinates of the peer.
+    pub remote: EthCoord,
+    /// Reserved (zero).
+    pub reserved: u32,
+}
+
+// SAFETY: `EthLink` consists of integers only, without padding.
+unsafe impl AsBytes for EthLink {}
+
+/// The result of [`IOCTL_GET_TOPOLOGY`]: the coordinates of the chip and its
+/// Ethernet links (by port).
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct Topology {
+    /// The coordinates of the chip.
+    pub local: EthCoord,
+    /// The number of ports (valid entries of `links`).
+    pub link_count: u32,
+    /// The links, by port.
+    pub links: [EthLink; MAX_ETH_LINKS],
+}
+
+// SAFETY: `Topology` consists of integers only, without padding.
+unsafe impl AsBytes for Topology {}
+
+/// The result of [`IOCTL_GET_HARVESTING`]: the units of the chip disabled by
+/// harvesting, and the size of the full grid of the architecture.
+///
+/// Each mask has one bit per unit of the grid: Tensix rows and columns in
+/// ascending NOC0 order (e.g., bit 0 of `tensix_rows` is the lowest row holding
+/// Tensix cores), DRAM banks by bank and Ethernet cores by port.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct Harvesting {
+    /// The disabled Tensix rows.
+    pub tensix_rows: u32,
+    /// The disabled Tensix columns.
+    pub tensix_cols: u32,
+    /// The disabled DRAM banks.
+    pub dram_banks: u32,
+    /// The disabled Ethernet cores.
+    pub eth_cores: u32,
+    /// The number of Tensix rows of the grid.
+    pub tensix_row_count: u32,
+    /// The number of Tensix columns of the grid.
+    pub tensix_col_count: u32,
+    /// The number of DRAM banks of the grid.
+    pub dram_bank_count: u32,
+    /// The number of Ethernet cores of the grid.
+    pub eth_core_count: u32,
+    /// The harvesting mask as reported by the firmware.
+    pub raw: u32,
+    /// Reserved (zero).
+    pub reserved: u32,
+}
+
+// SAFETY: `Harvesting` consists of integers only, without padding.
+unsafe impl AsBytes for Harvesting {}
+
+/// The result of [`IOCTL_FLASH_INFO`]: the layout of the SPI flash of the board.
+///
+/// Reads, erases and writes address whole blocks. Only the blocks within
+/// `writable_start .. writable_end` may be erased or written, the rest (e.g.,
+/// the board identity) is read-only.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct FlashInfo {
+    /// The size of the flash.
+    pub size: u32,
+    /// The size of a block.
+    pub block_size: u32,
+    /// The start of the writable region.
+    pub writable_start: u32,
+    /// The end of the writable region.
+    pub writable_end: u32,
+    /// Whether erasing and writing is refused (the `flash_write_protect`
+    /// parameter is set).
+    pub write_protected: u32,
+    /// Reserved (zero).
+    pub reserved: u32,
+}
+
+// SAFETY: `FlashInfo` consists of integers only, without padding.
+unsafe impl AsBytes for FlashInfo {}
+
+/// The argument of [`IOCTL_FLASH_READ`], [`IOCTL_FLASH_ERASE`] and
+/// [`IOCTL_FLASH_WRITE`].
+///
+/// For reads and erases, `offset` and `length` give the (block-aligned) range
+/// of the flash, and `data` the user buffer to read into (unused for erases).
+/// For writes, `data` and `length` give the bundle (see [`FlashBundle`]), which
+/// holds the range to write, and `offset` must be zero.
+#[repr(C)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct FlashIo {
+    /// The offset within the flash.
+    pub offset: u32,
+    /// The length of the range or bundle.
+    pub length: u32,
+    /// The user address of the buffer or bundle.
+    pub data: u64,
+}
+
+// SAFETY: `FlashIo` consists of integers only, so any bit pattern is valid.
+unsafe impl FromBytes for FlashIo {}
+
+/// The magic of a [`FlashBundle`] (`"TTFB"` in memory).
+pub const FLASH_BUNDLE_MAGIC: u32 = u32::from_le_bytes(*b"TTFB");
+
+/// The version of the [`FlashBundle`] layout.
+pub const FLASH_BUNDLE_VERSION: u32 = 1;
+
+/// The maximum length of the signature of a [`FlashBundle`].
+pub const FLASH_SIGNATURE_MAX: u32 = 0x1_0000;
+
+/// The header of a bundle written with [`IOCTL_FLASH_WRITE`].
+///
+/// The header is followed by the signature (`signature_length` bytes) and the
+/// image (`length` bytes), which is written to the flash at `offset`. The
+/// signature is a detached PKCS#7 signature of the header (with
+/// `signature_length` zero) followed by the image, by a key trusted by the
+/// kernel (as for signed modules), and `device_id` is the PCI device ID of the
+/// chips the image is built for.
+#[repr(C)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct FlashBundle {
+    /// The magic ([`FLASH_BUNDLE_MAGIC`]).
+    pub magic: u32,
+    /// The version of the layout ([`FLASH_BUNDLE_VERSION`]).
+    pub version: u32,
+    /// The PCI device ID of the chips the image is built for.
+    pub device_id: u32,
+    /// The offset of the image within the flash.
+    pub offset: u32,
+    /// The length of the image.
+    pub length: u32,
+    /// The length of the signature.
+    pub signature_length: u32,
+}
+
+// SAFETY: `FlashBundle` consists of integers only, so any bit pattern is valid.
+unsafe impl FromBytes for FlashBundle {}
+
+/// The power and clock limits of a chip: the argument of
+/// [`IOCTL_SET_POWER_LIMITS`].
+///
+/// All limits are set at once, so a limit is changed by getting the limits
+/// (with [`IOCTL_GET_POWER_LIMITS`]), changing it, and setting them again.
+/// Each limit must be within the bounds of the board, and `aiclk_min` must not
+/// exceed `aiclk_max`.
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct PowerLimits {
+    /// The minimum AI clock in MHz.
+    pub aiclk_min: u32,
+    /// The maximum AI clock in MHz.
+    pub aiclk_max: u32,
+    /// The power limit of the chip in watts.
+    pub power_limit: u32,
+    /// The temperature at which the chip is throttled in degrees Celsius.
+    pub thermal_limit: u32,
+}
+
+// SAFETY: `PowerLimits` consists of integers only, without padding.
+unsafe impl AsBytes for PowerLimits {}
+
+// SAFETY: `PowerLimits` consists of integers only, so any bit pattern is valid.
+unsafe impl FromBytes for PowerLimits {}
+
+/// The result of [`IOCTL_GET_POWER_LIMITS`]: the limits of the chip, and the
+/// bounds and defaults of its board.
+///
+/// Each limit may be set within `min ..= max` (e.g., the AI clock limits within
+/// `min.aiclk_min ..= max.aiclk_max`). The defaults apply at probe and are
+/// restored when the driver is unloaded.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct PowerInfo {
+    /// The current limits.
+    pub limits: PowerLimits,
+    /// The lower bounds of the limits.
+    pub min: PowerLimits,
+    /// The upper bounds of the limits.
+    pub max: PowerLimits,
+    /// The default limits of the board.
+    pub defaults: PowerLimits,
+}
+
+// SAFETY: `PowerInfo` consists of integers only, without padding.
+unsafe impl AsBytes for PowerInfo {}
+
+/// The [`PowerState`] of a file which does not use the chip.
+pub const POWER_STATE_IDLE: u32 = 0;
+
+/// The [`PowerState`] of a file which is about to use the chip.
+pub const POWER_STATE_BUSY: u32 = 1;
+
+/// The argument of [`IOCTL_SET_POWER_STATE`].
+///
+/// The chip leaves its low-power state (ramping up its clocks) when the first
+/// file hints that it is busy, and enters it again once no file is busy (when
+/// the last busy file hints that it is idle or is closed). Files start idle.
+#[repr(C)]
+#[allow(unreachable_pub, reason = "rust-for-linux")]
+pub struct PowerState {
+    /// The state of the file ([`POWER_STATE_IDLE`] or [`POWER_STATE_BUSY`]).
+    pub state: u32,
+    /// Reserved (zero).
+    pub reserved: u32,
+}
+
+// SAFETY: `PowerState` consists of integers only, so any bit pattern is valid.
+unsafe impl FromBytes for PowerState {}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: GPL-2.0
+
+/*
+ * Signature verification of images written to the chips.
+ *
+ * The keyring and usage constants are macros and enumerators of
+ * `<linux/verification.h>`, which is not part of the kernel bindings, so the
+ * check is wrapped here and called from Rust (see verify.rs).
+ */
+
+#include <linux/errno.h>
+#include <linux/verification.h>
+
+int tt_verify_signature(const void *data, size_t len, const void *sig, size_t sig_len);
+
+int tt_verify_signature(const void *data, size_t len, const void *sig, size_t sig_len)
+{
+#ifdef CONFIG_SYSTEM_DATA_VERIFICATION
+	return verify_pkcs7_signature(data, len, sig, sig_len, VERIFY_USE_SECONDARY_KEYRING,
+				      VERIFYING_FIRMWARE_SIGNATURE, NULL, NULL);
+#else
+	return -EOPNOTSUPP;
+#endif
+}
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: GPL-2.0
+
+// NOTE: The check is implemented by the C wrapper in `verify.c`, since the
+// keyring and usage constants it needs are not part of the kernel bindings.
+
+use ::kernel::{
+    ffi::{c_int, c_void},
+    prelude::*,
+};
+
+unsafe extern "C" {
+    /// Verifies the detached PKCS#7 signature `sig` of `data`.
+    fn tt_verify_signature(data: *const c_void, len: usize, sig: *const c_void, sig_len: usize) -> c_int;
+}
+
+/// Verifies that `sig` is a detached PKCS#7 signature of `data` by a key of the
+/// secondary trusted keyring (which includes the keys trusted for modules).
+///
+/// # Errors
+///
+/// * Errors with `EOPNOTSUPP` if the kernel was built without
+///   `CONFIG_SYSTEM_DATA_VERIFICATION`.
+/// * Errors with `EKEYREJECTED` if the signature does not match.
+/// * Errors with `ENOKEY` if the signing key is not trusted.
+/// * Errors if parsing the signature fails.
+pub(crate) fn signature(data: &[u8], sig: &[u8]) -> Result {
+    // SAFETY: `data` and `sig` are valid for reads of their lengths for the
+    // duration of the call, which does not retain them.
+    let err = unsafe { tt_verify_signature(data.as_ptr().cast(), data.len(), sig.as_ptr().cast(), sig.len()) };
+    ::kernel::error::to_result(err)
+}
@@ -66,9 +66,15 @@ use ::kernel::prelude::*;
 #[pin_data]
 struct TtDriverModule {
     module: &'static ThisModule,
-    compat: crate::tt::compat::TenstorrentCompat,
+    // NOTE: `pci_reg` and `emu` are declared before `compat`, `reboot` and
+    // `debugfs` so that they are dropped first, since bound (or emulated)
+    // devices may hold entries in each.
     #[pin]
     pci_reg: ::kernel::driver::Registration<::kernel::pci::Adapter<crate::tt::device::pci::TtPci>>,
+    emu: crate::tt::device::emu::TtEmuDevices,
+    compat: crate::tt::compat::TenstorrentCompat,
+    reboot: crate::tt::reboot::TtReboot,
+    debugfs: crate::tt::debugfs::TtDebugfs,
 }
 
 module! {
@@ -80,12 +86,24 @@ module! {
     params: {
         unsafe_compatibility_overlay: u8 {
             default: 0,
-            description: "Enable the official Tenstorrent driver compatibility overlay. May cause a kernel WARN and taint if raced with the official driver. For development and testing only.",
+            description: "Enable the official Tenstorrent driver compatibility overlay. The overlay is removed while the official driver is loaded. For development and testing only.",
+        },
+        unsafe_compatibility_overlay_restore: u8 {
+            default: 1,
+            description: "Recreate the compatibility overlay after the official Tenstorrent driver unloads.",
         },
         auto_reset_timeout: u8 {
             default: 10,
             description: "Timeout duration in seconds for M3 auto reset to occur.",
         },
+        dev_mode: u16 {
+            default: 0,
+            description: "Permission bits of the device nodes (e.g., 0660 together with dev_gid), 0 for the default.",
+        },
+        dev_gid: i32 {
+            default: -1,
+            description: "Group (in the initial user namespace) of the device nodes, -1 for the default.",
+        },
         dma_address_bits: u32 {
             default: 0,
             description: "DMA address bits, 0 for automatic.",
@@ -94,10 +112,42 @@ module! {
             default: 32,
             description: "Maximum number of tenstorrent devices (chips) to support.",
         },
+        unsafe_debugfs_regs: u8 {
+            default: 0,
+            description: "Expose raw BAR0 register access in debugfs. For development and debugging only.",
+        },
         reset_limit: u32 {
             default: 10,
             description: "Maximum number of times to reset device during boot.",
         },
+        unsafe_flash_access: u8 {
+            default: 0,
+            description: "Transfer SPI flash blocks through ARC firmware messages which are not yet verified against a firmware interface. For development only.",
+        },
+        unsafe_irq_sources: u8 {
+            default: 0,
+            description: "Allocate MSI vectors for the interrupt sources (ARC replies and firmware faults), whose vectors are not yet verified against a firmware interface. For development only.",
+        },
+        flash_write_protect: u8 {
+            default: 1,
+            description: "Refuse erasing and writing the SPI flash of the boards through the device nodes, 0 to allow firmware updates.",
+        },
+        firmware_autoload: u8 {
+            default: 0,
+            description: "Load the firmware images of the chips (e.g., the Wormhole Ethernet firmware) on probe, reset and resume. Not yet verified on hardware.",
+        },
+        heartbeat_timeout: u32 {
+            default: 10,
+            description: "Seconds without a change of the firmware heartbeat after which a chip is considered hung, 0 to disable the watchdog.",
+        },
+        heartbeat_auto_reset: u8 {
+            default: 0,
+            description: "Reset chips whose firmware is considered hung by the heartbeat watchdog.",
+        },
+        emulated_devices: u32 {
+            default: 0,
+            description: "Number of software-emulated Wormhole devices to create. For testing without hardware only.",
+        },
     },
 }
 
@@ -106,12 +156,15 @@ impl ::kernel::InPlaceModule for TtDriverModule {
         try_pin_init!(Self {
             module,
             compat: crate::tt::compat::TenstorrentCompat::new(module)?,
+            reboot: crate::tt::reboot::TtReboot::new()?,
+            debugfs: crate::tt::debugfs::TtDebugfs::new(),
             pci_reg <- {
                 let version = crate::tt::version()?;
                 let version = version.to_str()?;
                 pr_info!("(init): {version}\n");
                 ::kernel::driver::Registration::new(<Self as ::kernel::ModuleMetadata>::NAME, module)
             },
+            emu: crate::tt::device::emu::TtEmuDevices::new()?,
         })
     }
 }